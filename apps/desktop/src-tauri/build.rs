@@ -1,3 +1,39 @@
 fn main() {
-    tauri_build::build()
+    // App commands are only allowed in the windows of the capabilities that list them,
+    // so that e.g. browser games served by the app can't use them.
+    tauri_build::try_build(tauri_build::Attributes::new().app_manifest(
+        tauri_build::AppManifest::new().commands(&[
+            "get_games",
+            "fetch_game_versions",
+            "fetch_game_version_info",
+            "download_game",
+            "install_game_from_file",
+            "update_game",
+            "switch_game_version",
+            "verify_game",
+            "repair_game",
+            "check_for_updates",
+            "launch_game",
+            "get_launch_targets",
+            "set_launch_target",
+            "uninstall_game",
+            "hide_game",
+            "get_settings",
+            "update_settings",
+            "find_wine_runner",
+            "get_collections",
+            "get_collection_games",
+            "itchio_login",
+            "itchio_totp_verify",
+            "itchio_logout",
+            "itchio_import_installs",
+            "legacy_games_login",
+            "legacy_games_logout",
+            "get_downloads",
+            "pause_download",
+            "resume_download",
+            "cancel_download",
+        ]),
+    ))
+    .expect("failed to run tauri-build")
 }
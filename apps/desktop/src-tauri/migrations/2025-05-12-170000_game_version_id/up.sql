@@ -0,0 +1 @@
+ALTER TABLE `games` ADD COLUMN `version_id` TEXT;
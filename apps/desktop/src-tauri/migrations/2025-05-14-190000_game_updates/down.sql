@@ -0,0 +1,38 @@
+CREATE TABLE `games_old` (
+  `id` TEXT NOT NULL,
+  `source` TEXT CHECK (
+    source IN ('itchio', 'legacy_games', 'epic_games')
+  ) NOT NULL,
+  `title` TEXT NOT NULL,
+  `key` TEXT,
+  `developer` TEXT,
+  `launch_target` TEXT,
+  `path` TEXT,
+  `version` TEXT,
+  `status` TEXT CHECK (
+    status IN (
+      'installed',
+      'not_installed',
+      'downloading',
+      'installing',
+      'uninstalling'
+    )
+  ) NOT NULL,
+  `favorite` BOOLEAN NOT NULL DEFAULT FALSE,
+  `hidden` BOOLEAN NOT NULL DEFAULT FALSE,
+  `cover_url` TEXT,
+  `sort_title` TEXT NOT NULL,
+  `version_id` TEXT,
+  PRIMARY KEY (`id`, `source`)
+);
+
+INSERT INTO `games_old`
+SELECT
+  `id`, `source`, `title`, `key`, `developer`, `launch_target`, `path`, `version`,
+  CASE WHEN `status` = 'update_available' THEN 'installed' ELSE `status` END,
+  `favorite`, `hidden`, `cover_url`, `sort_title`, `version_id`
+FROM `games`;
+
+DROP TABLE `games`;
+
+ALTER TABLE `games_old` RENAME TO `games`;
@@ -0,0 +1,44 @@
+-- SQLite can't alter CHECK constraints, so the table has to be rebuilt
+-- to allow the new update_available status.
+CREATE TABLE `games_new` (
+  `id` TEXT NOT NULL,
+  `source` TEXT CHECK (
+    source IN ('itchio', 'legacy_games', 'epic_games')
+  ) NOT NULL,
+  `title` TEXT NOT NULL,
+  `key` TEXT,
+  `developer` TEXT,
+  `launch_target` TEXT,
+  `path` TEXT,
+  `version` TEXT,
+  `status` TEXT CHECK (
+    status IN (
+      'installed',
+      'not_installed',
+      'downloading',
+      'installing',
+      'uninstalling',
+      'update_available'
+    )
+  ) NOT NULL,
+  `favorite` BOOLEAN NOT NULL DEFAULT FALSE,
+  `hidden` BOOLEAN NOT NULL DEFAULT FALSE,
+  `cover_url` TEXT,
+  `sort_title` TEXT NOT NULL,
+  `version_id` TEXT,
+  `channel` TEXT,
+  PRIMARY KEY (`id`, `source`)
+);
+
+INSERT INTO `games_new` (
+  `id`, `source`, `title`, `key`, `developer`, `launch_target`, `path`, `version`,
+  `status`, `favorite`, `hidden`, `cover_url`, `sort_title`, `version_id`
+)
+SELECT
+  `id`, `source`, `title`, `key`, `developer`, `launch_target`, `path`, `version`,
+  `status`, `favorite`, `hidden`, `cover_url`, `sort_title`, `version_id`
+FROM `games`;
+
+DROP TABLE `games`;
+
+ALTER TABLE `games_new` RENAME TO `games`;
@@ -0,0 +1,8 @@
+CREATE TABLE `launch_targets` (
+  `game_id` TEXT NOT NULL,
+  `game_source` TEXT CHECK (
+    game_source IN ('itchio', 'legacy_games', 'epic_games')
+  ) NOT NULL,
+  `path` TEXT NOT NULL,
+  PRIMARY KEY (`game_id`, `game_source`, `path`)
+);
@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS `collection_games`;
+
+DROP TABLE IF EXISTS `collections`;
+
+ALTER TABLE `games` DROP COLUMN `store_url`;
+ALTER TABLE `games` DROP COLUMN `owned`;
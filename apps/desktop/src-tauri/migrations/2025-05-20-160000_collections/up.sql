@@ -0,0 +1,22 @@
+ALTER TABLE `games` ADD COLUMN `owned` BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE `games` ADD COLUMN `store_url` TEXT;
+
+CREATE TABLE `collections` (
+  `id` TEXT NOT NULL,
+  `source` TEXT CHECK (
+    source IN ('itchio', 'legacy_games', 'epic_games')
+  ) NOT NULL,
+  `title` TEXT NOT NULL,
+  PRIMARY KEY (`id`, `source`)
+);
+
+CREATE TABLE `collection_games` (
+  `collection_id` TEXT NOT NULL,
+  `source` TEXT CHECK (
+    source IN ('itchio', 'legacy_games', 'epic_games')
+  ) NOT NULL,
+  `game_id` TEXT NOT NULL,
+  `position` INTEGER NOT NULL,
+  `blurb` TEXT,
+  PRIMARY KEY (`collection_id`, `source`, `game_id`)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE `game_embeds` (
+  `game_id` TEXT NOT NULL,
+  `game_source` TEXT CHECK (
+    game_source IN ('itchio', 'legacy_games', 'epic_games')
+  ) NOT NULL,
+  `width` INTEGER NOT NULL,
+  `height` INTEGER NOT NULL,
+  `fullscreen` BOOLEAN NOT NULL DEFAULT FALSE,
+  PRIMARY KEY (`game_id`, `game_source`)
+);
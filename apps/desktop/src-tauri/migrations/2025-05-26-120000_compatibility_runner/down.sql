@@ -0,0 +1,4 @@
+ALTER TABLE `games` DROP COLUMN `platform`;
+
+ALTER TABLE `configs` DROP COLUMN `show_incompatible_versions`;
+ALTER TABLE `configs` DROP COLUMN `wine_runner_path`;
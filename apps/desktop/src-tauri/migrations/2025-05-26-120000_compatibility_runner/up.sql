@@ -0,0 +1,6 @@
+ALTER TABLE `configs` ADD COLUMN `wine_runner_path` TEXT;
+ALTER TABLE `configs` ADD COLUMN `show_incompatible_versions` BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE `games` ADD COLUMN `platform` TEXT CHECK (
+  platform IN ('windows', 'linux', 'macos', 'web')
+);
@@ -0,0 +1,2 @@
+ALTER TABLE `configs` DROP COLUMN `itchio_full_synced_at`;
+ALTER TABLE `configs` DROP COLUMN `itchio_synced_at`;
@@ -0,0 +1,2 @@
+ALTER TABLE `configs` ADD COLUMN `itchio_synced_at` BIGINT;
+ALTER TABLE `configs` ADD COLUMN `itchio_full_synced_at` BIGINT;
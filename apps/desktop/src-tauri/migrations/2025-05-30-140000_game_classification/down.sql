@@ -0,0 +1,3 @@
+ALTER TABLE `configs` DROP COLUMN `media_location`;
+
+ALTER TABLE `games` DROP COLUMN `classification`;
@@ -0,0 +1,18 @@
+ALTER TABLE `games` ADD COLUMN `classification` TEXT CHECK (
+  classification IN (
+    'game',
+    'tool',
+    'assets',
+    'game_mod',
+    'physical_game',
+    'soundtrack',
+    'other',
+    'comic',
+    'book'
+  )
+) NOT NULL DEFAULT 'game';
+
+ALTER TABLE `configs` ADD COLUMN `media_location` TEXT;
+
+-- The next itch.io sync has to go through every key to classify the existing games
+UPDATE `configs` SET `itchio_synced_at` = NULL, `itchio_full_synced_at` = NULL;
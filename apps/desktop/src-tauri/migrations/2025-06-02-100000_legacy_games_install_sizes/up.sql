@@ -0,0 +1,5 @@
+CREATE TABLE `legacy_games_install_sizes` (
+  `installer_uuid` TEXT PRIMARY KEY NOT NULL,
+  `install_size` BIGINT NOT NULL,
+  `estimated` BOOLEAN NOT NULL DEFAULT TRUE
+);
@@ -0,0 +1,13 @@
+CREATE TABLE `downloads` (
+  `game_id` TEXT NOT NULL,
+  `game_source` TEXT CHECK (
+    game_source IN ('itchio', 'legacy_games', 'epic_games')
+  ) NOT NULL,
+  `version_id` TEXT,
+  `download_options` TEXT NOT NULL,
+  `downloaded` BIGINT NOT NULL DEFAULT 0,
+  `paused` BOOLEAN NOT NULL DEFAULT FALSE,
+  `into_install` BOOLEAN NOT NULL DEFAULT FALSE,
+  `queued_at` BIGINT NOT NULL,
+  PRIMARY KEY (`game_id`, `game_source`)
+);
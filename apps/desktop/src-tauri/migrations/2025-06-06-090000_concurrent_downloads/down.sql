@@ -0,0 +1 @@
+ALTER TABLE `configs` DROP COLUMN `max_concurrent_downloads`;
@@ -0,0 +1 @@
+ALTER TABLE `configs` ADD COLUMN `max_concurrent_downloads` INTEGER NOT NULL DEFAULT 2;
@@ -0,0 +1 @@
+ALTER TABLE `downloads` ADD COLUMN `previous_version` TEXT;
@@ -0,0 +1,42 @@
+use crate::{
+    common::database,
+    models::{
+        collection::{Collection, CollectionGame},
+        game::{GameSource, ReducedGame},
+        payloads::CollectionGamePayload,
+    },
+};
+
+#[tauri::command]
+pub async fn get_collections() -> Result<Vec<Collection>, String> {
+    let mut connection = database::create_connection()?;
+    let collections = Collection::select(&mut connection)?;
+    Ok(collections)
+}
+
+#[tauri::command]
+pub async fn get_collection_games(
+    collection_id: String,
+    collection_source: GameSource,
+) -> Result<Vec<CollectionGamePayload>, String> {
+    let mut connection = database::create_connection()?;
+
+    let entries = CollectionGame::select(&mut connection, &collection_source, &collection_id)?;
+    let mut games =
+        ReducedGame::select_in_collection(&mut connection, &collection_source, &collection_id)?;
+
+    // Entries are already sorted by their position in the collection
+    let results = entries
+        .into_iter()
+        .filter_map(|entry| {
+            let index = games.iter().position(|game| game.id == entry.game_id)?;
+            Some(CollectionGamePayload {
+                game: games.swap_remove(index),
+                position: entry.position,
+                blurb: entry.blurb,
+            })
+        })
+        .collect();
+
+    Ok(results)
+}
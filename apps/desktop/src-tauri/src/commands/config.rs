@@ -0,0 +1,54 @@
+use crate::{
+    common::database,
+    managers::download::DownloadManager,
+    models::{config::Config, payloads::SettingsPayload},
+    util,
+};
+use std::sync::RwLock;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_settings(config: State<'_, RwLock<Config>>) -> SettingsPayload {
+    let config = config.read().unwrap();
+
+    SettingsPayload {
+        wine_runner_path: config.wine_runner_path(),
+        show_incompatible_versions: config.show_incompatible_versions(),
+        media_location: config.media_location(),
+        max_concurrent_downloads: config.max_concurrent_downloads(),
+    }
+}
+
+#[tauri::command]
+pub fn update_settings(
+    config: State<'_, RwLock<Config>>,
+    download_manager: State<'_, DownloadManager>,
+    settings: SettingsPayload,
+) -> Result<(), String> {
+    let mut connection = database::create_connection()?;
+    let mut config = config.write().unwrap();
+
+    let wine_runner_path = settings
+        .wine_runner_path
+        .filter(|runner_path| !runner_path.trim().is_empty());
+
+    config.set_wine_runner_path(wine_runner_path, &mut connection)?;
+    config.set_show_incompatible_versions(settings.show_incompatible_versions, &mut connection)?;
+
+    let media_location = settings
+        .media_location
+        .filter(|media_location| !media_location.trim().is_empty());
+
+    config.set_media_location(media_location, &mut connection)?;
+
+    config.set_max_concurrent_downloads(settings.max_concurrent_downloads, &mut connection)?;
+    download_manager.set_max_concurrent_downloads(config.max_concurrent_downloads());
+
+    Ok(())
+}
+
+/// Returns the path of the Wine runner that will be used, or nothing if none was found.
+#[tauri::command]
+pub fn find_wine_runner() -> Option<String> {
+    util::wine::find_runner().map(|runner| runner.to_string_lossy().into_owned())
+}
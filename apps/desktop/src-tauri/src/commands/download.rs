@@ -0,0 +1,47 @@
+use crate::{
+    managers::download::DownloadManager,
+    models::{game::GameSource, payloads::DownloadsPayload},
+};
+use tauri::State;
+
+#[tauri::command]
+pub fn get_downloads(
+    download_manager: State<'_, DownloadManager>,
+) -> Result<DownloadsPayload, String> {
+    Ok(download_manager.get_downloads()?)
+}
+
+#[tauri::command]
+pub fn pause_download(
+    download_manager: State<'_, DownloadManager>,
+    game_id: String,
+    game_source: GameSource,
+) -> Result<(), String> {
+    download_manager.pause_download(&game_source, &game_id)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn resume_download(
+    download_manager: State<'_, DownloadManager>,
+    game_id: String,
+    game_source: GameSource,
+) -> Result<(), String> {
+    download_manager.resume_download(&game_source, &game_id)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn cancel_download(
+    download_manager: State<'_, DownloadManager>,
+    game_id: String,
+    game_source: GameSource,
+) -> Result<(), String> {
+    download_manager
+        .cancel_download(&game_source, &game_id)
+        .await?;
+
+    Ok(())
+}
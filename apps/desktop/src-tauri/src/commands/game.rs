@@ -2,14 +2,22 @@ use crate::{
     common::database,
     managers::download::{DownloadManager, DownloadOptions},
     models::{
-        game::{Game, GameSource, GameStatus, GameVersion, GameVersionInfo, ReducedGame},
+        collection::Collection,
+        config::Config,
+        game::{
+            Game, GameSource, GameStatus, GameVerification, GameVersion, GameVersionInfo,
+            ReducedGame,
+        },
+        launch_target::LaunchTarget,
         payloads::GameFiltersPayload,
     },
-    storefronts::get_storefront,
+    storefronts::{self, get_storefront},
 };
+use std::{path::PathBuf, sync::RwLock};
 use strum::IntoEnumIterator;
-use tauri::{AppHandle, Emitter, State};
-use tokio::task::JoinSet;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_opener::OpenerExt;
+use tokio::{fs, task::JoinSet};
 
 #[tauri::command]
 pub async fn get_games(
@@ -39,6 +47,31 @@ pub async fn get_games(
         }
 
         Game::insert_or_ignore(&mut connection, &games_to_return)?;
+        Game::mark_owned(&mut connection, &games_to_return)?;
+        Game::update_classifications(&mut connection, &games_to_return)?;
+
+        for source in GameSource::iter() {
+            if let Err(e) = get_storefront(&source).read().await.finish_sync().await {
+                println!("{:?}", e);
+            }
+        }
+
+        // Collections are synced after the owned games, so that their entries are matched to them
+        for source in GameSource::iter() {
+            let fetched_collections = get_storefront(&source)
+                .read()
+                .await
+                .fetch_collections()
+                .await;
+
+            match fetched_collections {
+                Ok(Some(fetched_collections)) => {
+                    Collection::replace(&mut connection, &source, fetched_collections)?
+                }
+                Ok(None) => (),
+                Err(e) => println!("{:?}", e),
+            }
+        }
     }
 
     Game::refresh_installed(&mut connection)?;
@@ -83,6 +116,7 @@ pub async fn fetch_game_version_info(
 
 #[tauri::command]
 pub async fn download_game(
+    app: AppHandle,
     download_manager: State<'_, DownloadManager>,
     game_id: String,
     game_source: GameSource,
@@ -92,6 +126,10 @@ pub async fn download_game(
     let mut connection = database::create_connection()?;
     let mut game = Game::select_one(&mut connection, &game_source, &game_id)?;
 
+    if !game.classification.is_launchable() {
+        download_options.install_location = media_location(&app)?;
+    }
+
     let complete_install_location = download_options
         .install_location
         .join(game.title.replace(" :", " -").replace(":", " -"));
@@ -110,7 +148,7 @@ pub async fn download_game(
     match download {
         Ok(Some(download)) => {
             game.update(&mut connection).unwrap();
-            download_manager.enqueue_download(download);
+            download_manager.enqueue_download(download, game.installed_version(), None);
         }
         Ok(None) => {
             game.status = GameStatus::NotInstalled;
@@ -122,11 +160,200 @@ pub async fn download_game(
     Ok(())
 }
 
+/// Installs a game from a file downloaded outside of Fusion, e.g. a zip from the browser.
 #[tauri::command]
-pub async fn launch_game(game_id: String, game_source: GameSource) -> Result<(), String> {
+pub async fn install_game_from_file(
+    app: AppHandle,
+    game_id: String,
+    game_source: GameSource,
+    file_path: PathBuf,
+    install_location: PathBuf,
+) -> Result<(), String> {
     let mut connection = database::create_connection()?;
+    let mut game = Game::select_one(&mut connection, &game_source, &game_id)?;
+
+    let complete_install_location =
+        install_location.join(game.title.replace(" :", " -").replace(":", " -"));
+
+    // Restored if the install fails, e.g. when the game was already installed
+    let previous_game = game.clone();
+    let new_location = !complete_install_location.exists();
+
+    let result = get_storefront(&game_source)
+        .read()
+        .await
+        .install_from_file(&mut game, file_path, complete_install_location.clone())
+        .await;
+
+    if let Err(e) = result {
+        previous_game.update(&mut connection)?;
+        if new_location && complete_install_location.exists() {
+            fs::remove_dir_all(&complete_install_location)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        return Err(e.to_string());
+    }
+
     let game = Game::select_one(&mut connection, &game_source, &game_id)?;
 
+    app.emit("game-updated", &game).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_game(
+    download_manager: State<'_, DownloadManager>,
+    game_id: String,
+    game_source: GameSource,
+) -> Result<(), String> {
+    let mut connection = database::create_connection()?;
+    let mut game = Game::select_one(&mut connection, &game_source, &game_id)?;
+
+    let Some(version_id) = game.version_id.clone() else {
+        return Err("The game is not installed".into());
+    };
+
+    // The update may be in a different version, e.g. if the upload was replaced.
+    let version_id = get_storefront(&game_source)
+        .read()
+        .await
+        .fetch_game_update(&game)
+        .await?
+        .unwrap_or(version_id);
+
+    download_into_install(&download_manager, &mut connection, &mut game, version_id).await
+}
+
+#[tauri::command]
+pub async fn verify_game(
+    game_id: String,
+    game_source: GameSource,
+) -> Result<GameVerification, String> {
+    let mut connection = database::create_connection()?;
+    let game = Game::select_one(&mut connection, &game_source, &game_id)?;
+
+    let verification = get_storefront(&game_source)
+        .read()
+        .await
+        .verify_game(&game)
+        .await?;
+
+    Ok(verification)
+}
+
+/// Downloads what is needed to repair the damaged or missing files of an installed game.
+#[tauri::command]
+pub async fn repair_game(
+    download_manager: State<'_, DownloadManager>,
+    game_id: String,
+    game_source: GameSource,
+) -> Result<(), String> {
+    let mut connection = database::create_connection()?;
+    let mut game = Game::select_one(&mut connection, &game_source, &game_id)?;
+
+    let download = get_storefront(&game_source)
+        .read()
+        .await
+        .pre_repair(&mut game)
+        .await?;
+
+    if let Some(download) = download {
+        game.status = GameStatus::Downloading;
+        game.update(&mut connection)?;
+        download_manager.enqueue_download(
+            download,
+            game.installed_version(),
+            Some(game.installed_version()),
+        );
+    }
+
+    Ok(())
+}
+
+/// Switches an installed game to another version, e.g. from a stable to a beta channel.
+/// The new version is downloaded over the existing install.
+#[tauri::command]
+pub async fn switch_game_version(
+    download_manager: State<'_, DownloadManager>,
+    game_id: String,
+    game_source: GameSource,
+    version_id: String,
+) -> Result<(), String> {
+    let mut connection = database::create_connection()?;
+    let mut game = Game::select_one(&mut connection, &game_source, &game_id)?;
+
+    if game.version_id.as_ref() == Some(&version_id) {
+        return Err("This version is already installed".into());
+    }
+
+    download_into_install(&download_manager, &mut connection, &mut game, version_id).await
+}
+
+async fn download_into_install(
+    download_manager: &DownloadManager,
+    connection: &mut diesel::SqliteConnection,
+    game: &mut Game,
+    version_id: String,
+) -> Result<(), String> {
+    let Some(path) = game.path.clone() else {
+        return Err("The game is not installed".into());
+    };
+
+    let download_options = DownloadOptions {
+        install_location: PathBuf::from(path),
+    };
+    let previous_version = game.installed_version();
+
+    let download = get_storefront(&game.source)
+        .read()
+        .await
+        .pre_download(game, version_id, download_options)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(download) = download {
+        // The game keeps its version until the new one is installed, so that an update that
+        // doesn't complete is still offered
+        let version = game.installed_version();
+        game.set_installed_version(previous_version.clone());
+        game.status = GameStatus::Downloading;
+        game.update(connection)?;
+        download_manager.enqueue_download(download, version, Some(previous_version));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn check_for_updates() -> Result<(), String> {
+    storefronts::check_for_updates().await
+}
+
+#[tauri::command]
+pub async fn launch_game(
+    app: AppHandle,
+    game_id: String,
+    game_source: GameSource,
+) -> Result<(), String> {
+    let mut connection = database::create_connection()?;
+    let game = Game::select_one(&mut connection, &game_source, &game_id)?;
+
+    // Media is opened with the default application, e.g. a music player or a PDF reader
+    if !game.classification.is_launchable() {
+        let path = game.path.ok_or("The item is not installed")?;
+        let path = match game.launch_target {
+            Some(launch_target) => PathBuf::from(path).join(launch_target),
+            None => PathBuf::from(path),
+        };
+
+        return app
+            .opener()
+            .open_path(path.to_string_lossy(), None::<&str>)
+            .map_err(|e| e.to_string());
+    }
+
     get_storefront(&game_source)
         .read()
         .await
@@ -135,6 +362,46 @@ pub async fn launch_game(game_id: String, game_source: GameSource) -> Result<(),
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_launch_targets(
+    game_id: String,
+    game_source: GameSource,
+) -> Result<Vec<String>, String> {
+    let mut connection = database::create_connection()?;
+    let launch_targets = LaunchTarget::select(&mut connection, &game_source, &game_id)?;
+
+    Ok(launch_targets
+        .into_iter()
+        .map(|launch_target| launch_target.path)
+        .collect())
+}
+
+#[tauri::command]
+pub async fn set_launch_target(
+    app: AppHandle,
+    game_id: String,
+    game_source: GameSource,
+    launch_target: String,
+) -> Result<(), String> {
+    let mut connection = database::create_connection()?;
+    let mut game = Game::select_one(&mut connection, &game_source, &game_id)?;
+
+    let launch_targets = LaunchTarget::select(&mut connection, &game_source, &game_id)?;
+    if !launch_targets
+        .iter()
+        .any(|candidate| candidate.path == launch_target)
+    {
+        return Err("Invalid launch target".into());
+    }
+
+    game.launch_target = Some(launch_target);
+    game.update(&mut connection)?;
+
+    app.emit("game-updated", &game).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn uninstall_game(
     app: AppHandle,
@@ -157,9 +424,15 @@ pub async fn uninstall_game(
         .await?;
 
     game.path = None;
+    game.version = None;
+    game.version_id = None;
+    game.channel = None;
+    game.platform = None;
     game.status = GameStatus::NotInstalled;
     game.update(&mut connection)?;
 
+    LaunchTarget::delete(&mut connection, &game_source, &game_id)?;
+
     app.emit("game-uninstalled", &game)
         .map_err(|e| e.to_string())?;
 
@@ -182,3 +455,21 @@ pub async fn hide_game(
 
     Ok(())
 }
+
+/// Directory where media is downloaded to, which defaults to a folder in the downloads directory.
+fn media_location(app: &AppHandle) -> Result<PathBuf, String> {
+    let media_location = app
+        .state::<RwLock<Config>>()
+        .read()
+        .unwrap()
+        .media_location();
+
+    match media_location {
+        Some(media_location) => Ok(PathBuf::from(media_location)),
+        None => Ok(app
+            .path()
+            .download_dir()
+            .map_err(|e| e.to_string())?
+            .join("Fusion")),
+    }
+}
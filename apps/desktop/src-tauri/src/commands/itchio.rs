@@ -0,0 +1,165 @@
+use crate::{
+    common::database,
+    models::{
+        config::Config,
+        game::{Game, GameSource},
+        payloads::ItchioLoginPayload,
+    },
+    storefronts,
+};
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{AppHandle, Emitter, Manager, Url, WebviewUrl, WebviewWindow, WindowEvent};
+use tokio::sync::oneshot;
+use wrapper_itchio::{api::models::Login, ItchioClient};
+
+const RECAPTCHA_WINDOW_LABEL: &str = "itchio-recaptcha";
+
+#[tauri::command]
+pub async fn itchio_login(
+    app: AppHandle,
+    username: String,
+    password: String,
+) -> Result<ItchioLoginPayload, String> {
+    let mut login = ItchioClient::login(username.clone(), password.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if login.recaptcha_needed {
+        let recaptcha_url = login
+            .recaptcha_url
+            .ok_or("itch.io requested a reCAPTCHA without providing its URL")?;
+        let recaptcha_response = solve_recaptcha(&app, &recaptcha_url).await?;
+
+        login = ItchioClient::login_with_recaptcha(username, password, recaptcha_response)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    handle_login(&app, login).await
+}
+
+#[tauri::command]
+pub async fn itchio_totp_verify(
+    app: AppHandle,
+    code: String,
+    token: String,
+) -> Result<ItchioLoginPayload, String> {
+    let login = ItchioClient::totp_verify(code, token)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    handle_login(&app, login).await
+}
+
+#[tauri::command]
+pub async fn itchio_logout(app: AppHandle) -> Result<(), String> {
+    set_api_key(&app, None).await
+}
+
+/// Imports the games installed through the official itch app.
+#[tauri::command]
+pub async fn itchio_import_installs(app: AppHandle) -> Result<Vec<Game>, String> {
+    let api_key = app
+        .state::<RwLock<Config>>()
+        .read()
+        .unwrap()
+        .itchio_api_key()
+        .ok_or("Log in to itch.io to import games from the itch app")?;
+
+    let client = ItchioClient::new(api_key);
+    let games = storefronts::itchio::import_itch_app_installs(&client).await?;
+
+    for game in &games {
+        app.emit("game-updated", game).map_err(|e| e.to_string())?;
+    }
+
+    Ok(games)
+}
+
+/// Advances the login state machine: either the login is complete and the API key is stored,
+/// or a TOTP code is needed to continue.
+async fn handle_login(app: &AppHandle, login: Login) -> Result<ItchioLoginPayload, String> {
+    if login.totp_needed {
+        let token = login
+            .token
+            .ok_or("itch.io requested a TOTP code without providing a token")?;
+        return Ok(ItchioLoginPayload::TotpNeeded { token });
+    }
+
+    match login.key {
+        Some(key) if login.success => {
+            set_api_key(app, Some(key.key)).await?;
+            Ok(ItchioLoginPayload::Success)
+        }
+        _ => Err("Invalid username or password".into()),
+    }
+}
+
+/// Stores the API key and re-initializes the itch.io storefront with it.
+async fn set_api_key(app: &AppHandle, api_key: Option<String>) -> Result<(), String> {
+    let mut connection = database::create_connection()?;
+
+    {
+        let config = app.state::<RwLock<Config>>();
+        let mut config = config.write().unwrap();
+        config.set_itchio_api_key(api_key, &mut connection)?;
+
+        // The next sync has to go through every key of the new account
+        config.set_itchio_synced_at(None, &mut connection)?;
+        config.set_itchio_full_synced_at(None, &mut connection)?;
+    }
+
+    storefronts::init_storefront(&GameSource::Itchio).await
+}
+
+/// Opens the reCAPTCHA page in a webview and waits for the user to solve it.
+/// Returns the reCAPTCHA response token once itch.io redirects with it.
+async fn solve_recaptcha(app: &AppHandle, recaptcha_url: &str) -> Result<String, String> {
+    if let Some(window) = app.get_webview_window(RECAPTCHA_WINDOW_LABEL) {
+        window.close().map_err(|e| e.to_string())?;
+    }
+
+    let url: Url = recaptcha_url.parse().map_err(|_| "Invalid reCAPTCHA URL")?;
+
+    let (sender, receiver) = oneshot::channel::<Option<String>>();
+    let sender = Arc::new(Mutex::new(Some(sender)));
+    let navigation_sender = Arc::clone(&sender);
+
+    let window = WebviewWindow::builder(app, RECAPTCHA_WINDOW_LABEL, WebviewUrl::External(url))
+        .on_navigation(move |url| {
+            let response = url
+                .query_pairs()
+                .find(|(key, _)| key == "recaptcha_response" || key == "g-recaptcha-response")
+                .map(|(_, value)| value.into_owned());
+
+            match response {
+                Some(response) => {
+                    if let Some(sender) = navigation_sender.lock().unwrap().take() {
+                        let _ = sender.send(Some(response));
+                    }
+                    false
+                }
+                None => true,
+            }
+        })
+        .title("itch.io reCAPTCHA")
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    // Closing the window cancels the login
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            if let Some(sender) = sender.lock().unwrap().take() {
+                let _ = sender.send(None);
+            }
+        }
+    });
+
+    let response = receiver.await.ok().flatten();
+
+    if let Some(window) = app.get_webview_window(RECAPTCHA_WINDOW_LABEL) {
+        window.close().map_err(|e| e.to_string())?;
+    }
+
+    response.ok_or_else(|| "The reCAPTCHA was cancelled".into())
+}
@@ -0,0 +1,78 @@
+use crate::{
+    common::database,
+    models::{config::Config, game::GameSource, payloads::LegacyGamesLoginPayload},
+    storefronts,
+};
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager};
+use wrapper_legacygames::{
+    api::models::{GiveawayUser, Status, WpUser},
+    LegacyGamesClient,
+};
+
+/// Logs in to Legacy Games. Accounts that only have giveaway games are identified by their
+/// email, while WordPress accounts (with purchased games) also need a password.
+#[tauri::command]
+pub async fn legacy_games_login(
+    app: AppHandle,
+    email: String,
+    password: Option<String>,
+) -> Result<LegacyGamesLoginPayload, String> {
+    let email = email.trim().to_string();
+    let user_exists = LegacyGamesClient::fetch_user_exists(&email)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let giveaway_user = matches!(user_exists.data.giveaway_user, GiveawayUser::User { .. });
+
+    let token = match user_exists.data.wp_user {
+        WpUser::User { user_login, .. } => {
+            let Some(password) = password else {
+                return Ok(LegacyGamesLoginPayload::PasswordNeeded);
+            };
+
+            let token = LegacyGamesClient::generate_token(&user_login, &password);
+            let login = LegacyGamesClient::test_login(token.clone())
+                .await
+                .map_err(|e| e.to_string())?;
+
+            if !matches!(login.status, Status::Ok) || login.data.user_id.is_none() {
+                return Err(login
+                    .data
+                    .message
+                    .unwrap_or_else(|| "Invalid email or password".to_string()));
+            }
+
+            Some(token)
+        }
+        WpUser::False(_) if giveaway_user => None,
+        WpUser::False(_) => return Err("There is no Legacy Games account with this email".into()),
+    };
+
+    set_credentials(&app, Some(email), token).await?;
+
+    Ok(LegacyGamesLoginPayload::Success)
+}
+
+#[tauri::command]
+pub async fn legacy_games_logout(app: AppHandle) -> Result<(), String> {
+    set_credentials(&app, None, None).await
+}
+
+/// Stores the credentials and re-initializes the Legacy Games storefront with them.
+async fn set_credentials(
+    app: &AppHandle,
+    email: Option<String>,
+    token: Option<String>,
+) -> Result<(), String> {
+    let mut connection = database::create_connection()?;
+
+    {
+        let config = app.state::<RwLock<Config>>();
+        let mut config = config.write().unwrap();
+        config.set_legacy_games_email(email, &mut connection)?;
+        config.set_legacy_games_token(token, &mut connection)?;
+    }
+
+    storefronts::init_storefront(&GameSource::LegacyGames).await
+}
@@ -1 +1,6 @@
+pub mod collection;
+pub mod config;
+pub mod download;
 pub mod game;
+pub mod itchio;
+pub mod legacygames;
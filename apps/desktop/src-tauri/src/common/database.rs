@@ -1,12 +1,15 @@
 use super::result::Result;
 use crate::APP;
-use diesel::{Connection, SqliteConnection};
+use diesel::{connection::SimpleConnection, Connection, SqliteConnection};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use std::{fs, sync::OnceLock};
 use tauri::Manager;
 
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 const DATABASE_NAME: &str = "app_data.db";
+/// How long a connection waits for the database to be unlocked, e.g. while several
+/// downloads save their progress, before failing.
+const BUSY_TIMEOUT_MS: u32 = 5000;
 
 static URI: OnceLock<String> = OnceLock::new();
 
@@ -25,7 +28,8 @@ pub fn init() -> Result<()> {
 }
 
 pub fn create_connection() -> Result<SqliteConnection> {
-    let connection =
+    let mut connection =
         SqliteConnection::establish(URI.get().expect("Error: URI is empty!").as_str())?;
+    connection.batch_execute(&format!("PRAGMA busy_timeout = {};", BUSY_TIMEOUT_MS))?;
     Ok(connection)
 }
@@ -8,6 +8,8 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
+    /// A downloaded file doesn't have the expected hash.
+    ChecksumMismatch(String),
     Diesel(diesel::result::Error),
     DieselConnection(diesel::ConnectionError),
     Io(io::Error),
@@ -16,6 +18,7 @@ pub enum Error {
     Reqwest(reqwest::Error),
     Tauri(tauri::Error),
     Other(String),
+    WrapperItchio(wrapper_itchio::result::Error),
     WrapperLegacygames(wrapper_legacygames::result::Error),
 }
 
@@ -81,6 +84,12 @@ impl From<&str> for Error {
     }
 }
 
+impl From<wrapper_itchio::result::Error> for Error {
+    fn from(e: wrapper_itchio::result::Error) -> Self {
+        Self::WrapperItchio(e)
+    }
+}
+
 impl From<wrapper_legacygames::result::Error> for Error {
     fn from(e: wrapper_legacygames::result::Error) -> Self {
         Self::WrapperLegacygames(e)
@@ -90,6 +99,7 @@ impl From<wrapper_legacygames::result::Error> for Error {
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
+            Self::ChecksumMismatch(e) => write!(f, "Checksum mismatch: {}", e),
             Self::Diesel(e) => write!(f, "Diesel error: {}", e),
             Self::DieselConnection(e) => write!(f, "Diesel connection error: {}", e),
             Self::Io(e) => write!(f, "IO error: {}", e),
@@ -98,6 +108,7 @@ impl Display for Error {
             Self::Reqwest(e) => write!(f, "Reqwest error: {}", e),
             Self::Tauri(e) => write!(f, "Tauri error: {}", e),
             Self::Other(e) => write!(f, "Other error: {}", e),
+            Self::WrapperItchio(e) => write!(f, "Wrapper-Itchio error: {}", e),
             Self::WrapperLegacygames(e) => write!(f, "Wrapper-Legacygames error: {}", e),
         }
     }
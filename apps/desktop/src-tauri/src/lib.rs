@@ -23,14 +23,45 @@ pub async fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_opener::init())
+        .register_asynchronous_uri_scheme_protocol(
+            util::html::GAME_PROTOCOL,
+            |_context, request, responder| {
+                tauri::async_runtime::spawn(async move {
+                    responder.respond(util::html::handle_protocol_request(request).await);
+                });
+            },
+        )
         .invoke_handler(tauri::generate_handler![
             commands::game::get_games,
             commands::game::fetch_game_versions,
             commands::game::fetch_game_version_info,
             commands::game::download_game,
+            commands::game::install_game_from_file,
+            commands::game::update_game,
+            commands::game::switch_game_version,
+            commands::game::verify_game,
+            commands::game::repair_game,
+            commands::game::check_for_updates,
             commands::game::launch_game,
+            commands::game::get_launch_targets,
+            commands::game::set_launch_target,
             commands::game::uninstall_game,
-            commands::game::hide_game
+            commands::game::hide_game,
+            commands::config::get_settings,
+            commands::config::update_settings,
+            commands::config::find_wine_runner,
+            commands::collection::get_collections,
+            commands::collection::get_collection_games,
+            commands::itchio::itchio_login,
+            commands::itchio::itchio_totp_verify,
+            commands::itchio::itchio_logout,
+            commands::itchio::itchio_import_installs,
+            commands::legacygames::legacy_games_login,
+            commands::legacygames::legacy_games_logout,
+            commands::download::get_downloads,
+            commands::download::pause_download,
+            commands::download::resume_download,
+            commands::download::cancel_download,
         ])
         .setup(|app| {
             APP.set(app.handle().clone())
@@ -49,6 +80,21 @@ pub async fn run() {
                 storefronts::init_storefronts()
                     .await
                     .expect("Error initializing storefronts");
+
+                // The downloads are rebuilt through the storefronts, so they must be initialized
+                if let Err(e) = APP
+                    .get()
+                    .unwrap()
+                    .state::<DownloadManager>()
+                    .restore_downloads()
+                    .await
+                {
+                    println!("Error restoring downloads: {}", e);
+                }
+
+                if let Err(e) = storefronts::check_for_updates().await {
+                    println!("Error checking for updates: {}", e);
+                }
             });
 
             Ok(())
@@ -1,120 +1,405 @@
 use crate::{
-    models::{game::GameSource, payloads::DownloadPayload},
+    common::{
+        database,
+        result::{Error, Result},
+    },
+    models::{
+        config::Config,
+        download::DownloadEntry,
+        game::{Game, GameSource, GameStatus, InstalledVersion},
+        payloads::{DownloadFailedPayload, DownloadPayload, DownloadsPayload},
+    },
     storefronts::get_storefront,
     APP,
 };
-use reqwest::RequestBuilder;
-use serde::Deserialize;
+use diesel::SqliteConnection;
+use reqwest::{header::RANGE, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::VecDeque,
-    path::PathBuf,
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicU64, Ordering},
-        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
     },
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
 use tokio::{
-    fs::{self, OpenOptions},
-    io::AsyncWriteExt,
-    sync::{mpsc, Notify},
+    fs::{self, File, OpenOptions},
+    io::{AsyncReadExt, AsyncWriteExt},
+    sync::{mpsc, watch, Notify},
     task, time,
 };
 
+/// Number of times a file is tried before the download fails.
+const MAX_ATTEMPTS: u32 = 5;
+/// Delay before the first retry, doubled after each attempt.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+/// Appended to the names of the files being downloaded.
+const PARTIAL_EXTENSION: &str = ".partial";
+
 pub struct Download {
-    pub request: RequestBuilder,
-    pub file_name: String,
+    pub files: Vec<DownloadFile>,
     pub game_source: GameSource,
     pub game_id: String,
     pub game_title: String,
-    pub md5: Option<String>,
     pub download_size: u64,
     pub download_options: DownloadOptions,
+    /// Version being downloaded, used to rebuild the download after a restart.
+    /// Downloads that can't be rebuilt, e.g. repairs, have none.
+    pub version_id: Option<String>,
+}
+
+impl Download {
+    fn payload(&self, downloaded: u64) -> DownloadPayload {
+        DownloadPayload {
+            game_id: self.game_id.clone(),
+            game_source: self.game_source.clone(),
+            game_title: self.game_title.clone(),
+            download_size: self.download_size,
+            downloaded,
+        }
+    }
+
+    fn is(&self, game_source: &GameSource, game_id: &str) -> bool {
+        &self.game_source == game_source && self.game_id == game_id
+    }
+}
+
+/// A single file of a download. Files are downloaded in order, and their names
+/// are relative to the install location.
+pub struct DownloadFile {
+    pub request: RequestBuilder,
+    pub file_name: String,
+    pub md5: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DownloadOptions {
     pub install_location: PathBuf,
 }
 
+/// A download waiting in the queue. Resumed downloads continue from their partial files.
+struct QueuedDownload {
+    download: Download,
+    resume: bool,
+    /// Version installed by the download, saved to the game once it is installed.
+    version: InstalledVersion,
+    /// Version of the game before the download, for downloads into an existing install.
+    previous_version: Option<InstalledVersion>,
+}
+
+/// A running download, with the control to pause or cancel it.
+struct ActiveDownload {
+    control: watch::Sender<DownloadControl>,
+    payload: DownloadPayload,
+}
+
+/// Requests sent to a running download.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum DownloadControl {
+    Run,
+    Pause,
+    Cancel,
+}
+
+enum DownloadOutcome {
+    Finished,
+    Paused { downloaded: u64 },
+    Cancelled,
+    Failed { reason: String },
+}
+
 pub struct DownloadManager {
-    queue: Arc<Mutex<VecDeque<Download>>>,
+    queue: Arc<Mutex<VecDeque<QueuedDownload>>>,
     queue_notifier: Arc<Notify>,
+    paused: Arc<Mutex<Vec<QueuedDownload>>>,
+    /// Running downloads, by game.
+    active: Arc<Mutex<HashMap<(GameSource, String), ActiveDownload>>>,
+    /// Number of downloads that can run at the same time.
+    max_concurrent: Arc<AtomicUsize>,
 }
 
 impl DownloadManager {
     pub fn init() -> Self {
+        let max_concurrent = APP
+            .get()
+            .unwrap()
+            .state::<RwLock<Config>>()
+            .read()
+            .unwrap()
+            .max_concurrent_downloads();
+
         let manager = Self {
             queue: Arc::new(Mutex::new(VecDeque::new())),
             queue_notifier: Arc::new(Notify::new()),
+            paused: Arc::new(Mutex::new(Vec::new())),
+            active: Arc::new(Mutex::new(HashMap::new())),
+            max_concurrent: Arc::new(AtomicUsize::new(max_concurrent as usize)),
         };
 
         manager.process_queue();
         manager
     }
 
-    pub fn enqueue_download(&self, download: Download) {
-        let payload = DownloadPayload {
-            game_id: download.game_id.clone(),
-            game_source: download.game_source.clone(),
-            game_title: download.game_title.clone(),
-            download_size: download.download_size,
-            downloaded: 0,
+    /// Adds a download to the queue. Downloads into an existing install, e.g. updates, give
+    /// the version of the game before the download, which is restored if the install fails.
+    pub fn enqueue_download(
+        &self,
+        download: Download,
+        version: InstalledVersion,
+        previous_version: Option<InstalledVersion>,
+    ) {
+        let payload = download.payload(0);
+
+        if let Err(e) = save_download(&download, previous_version.as_ref()) {
+            println!("Error saving download: {}", e);
+        }
+
+        self.queue.lock().unwrap().push_back(QueuedDownload {
+            download,
+            resume: false,
+            version,
+            previous_version,
+        });
+
+        emit_event("download-queued", payload);
+
+        self.queue_notifier.notify_one();
+    }
+
+    /// Pauses a download. A running download stops after its current chunk and keeps its
+    /// partial files, while a queued one is simply set aside.
+    pub fn pause_download(&self, game_source: &GameSource, game_id: &str) -> Result<()> {
+        let key = (game_source.clone(), game_id.to_string());
+        if let Some(active) = self.active.lock().unwrap().get(&key) {
+            active.control.send_replace(DownloadControl::Pause);
+            return Ok(());
+        }
+
+        let mut queue = self.queue.lock().unwrap();
+        let index = queue
+            .iter()
+            .position(|queued| queued.download.is(game_source, game_id))
+            .ok_or("The game is not being downloaded")?;
+        let queued = queue.remove(index).unwrap();
+
+        let mut connection = database::create_connection()?;
+        DownloadEntry::set_paused(&mut connection, game_source, game_id, true)?;
+
+        APP.get()
+            .unwrap()
+            .emit("download-paused", queued.download.payload(0))?;
+
+        self.paused.lock().unwrap().push(queued);
+
+        Ok(())
+    }
+
+    /// Cancels a download, whether it is running, queued or paused.
+    /// Its files are removed and the game is restored to its state before the download.
+    pub async fn cancel_download(&self, game_source: &GameSource, game_id: &str) -> Result<()> {
+        let key = (game_source.clone(), game_id.to_string());
+        if let Some(active) = self.active.lock().unwrap().get(&key) {
+            active.control.send_replace(DownloadControl::Cancel);
+            return Ok(());
+        }
+
+        let queued = {
+            let mut queue = self.queue.lock().unwrap();
+            let mut paused = self.paused.lock().unwrap();
+
+            if let Some(index) = queue
+                .iter()
+                .position(|queued| queued.download.is(game_source, game_id))
+            {
+                queue.remove(index)
+            } else {
+                paused
+                    .iter()
+                    .position(|queued| queued.download.is(game_source, game_id))
+                    .map(|index| paused.remove(index))
+            }
+        };
+
+        let Some(queued) = queued else {
+            return Err("The game is not being downloaded".into());
+        };
+
+        discard_download(&queued.download, queued.previous_version.as_ref()).await?;
+        emit_event("download-cancelled", queued.download.payload(0));
+
+        Ok(())
+    }
+
+    /// Puts a paused download back in the queue.
+    pub fn resume_download(&self, game_source: &GameSource, game_id: &str) -> Result<()> {
+        let queued = {
+            let mut paused = self.paused.lock().unwrap();
+            let index = paused
+                .iter()
+                .position(|queued| queued.download.is(game_source, game_id))
+                .ok_or("The download is not paused")?;
+            paused.remove(index)
         };
 
-        self.queue.lock().unwrap().push_back(download);
+        let mut connection = database::create_connection()?;
+        DownloadEntry::set_paused(&mut connection, game_source, game_id, false)?;
 
-        APP.get().unwrap().emit("download-queued", payload).unwrap();
+        APP.get()
+            .unwrap()
+            .emit("download-resumed", queued.download.payload(0))?;
 
+        self.queue.lock().unwrap().push_back(queued);
         self.queue_notifier.notify_one();
+
+        Ok(())
     }
 
+    /// Restores the downloads that were queued when the app was closed. They are rebuilt
+    /// through the storefronts, which must be initialized.
+    pub async fn restore_downloads(&self) -> Result<()> {
+        let mut connection = database::create_connection()?;
+
+        for entry in DownloadEntry::select(&mut connection)? {
+            let queued = match rebuild_download(&mut connection, &entry).await {
+                Ok(Some(queued)) => queued,
+                result => {
+                    if let Err(e) = result {
+                        println!("Error restoring download: {}", e);
+                    }
+                    if let Err(e) = abandon_download(&mut connection, &entry).await {
+                        println!("Error abandoning download: {}", e);
+                    }
+                    continue;
+                }
+            };
+
+            let payload = queued.download.payload(entry.downloaded as u64);
+
+            APP.get().unwrap().emit("download-queued", &payload)?;
+
+            if entry.paused {
+                APP.get().unwrap().emit("download-paused", &payload)?;
+                self.paused.lock().unwrap().push(queued);
+            } else {
+                self.queue.lock().unwrap().push_back(queued);
+            }
+        }
+
+        self.queue_notifier.notify_one();
+
+        Ok(())
+    }
+
+    /// Lists the running, queued and paused downloads, with their saved progress.
+    pub fn get_downloads(&self) -> Result<DownloadsPayload> {
+        let mut connection = database::create_connection()?;
+        let progress: HashMap<(GameSource, String), u64> = DownloadEntry::select(&mut connection)?
+            .into_iter()
+            .map(|entry| ((entry.game_source, entry.game_id), entry.downloaded as u64))
+            .collect();
+
+        let with_progress = |payload: DownloadPayload| DownloadPayload {
+            downloaded: progress
+                .get(&(payload.game_source.clone(), payload.game_id.clone()))
+                .copied()
+                .unwrap_or_default(),
+            ..payload
+        };
+
+        let active = self
+            .active
+            .lock()
+            .unwrap()
+            .values()
+            .map(|active| with_progress(active.payload.clone()))
+            .collect();
+        let queued = self
+            .queue
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|queued| with_progress(queued.download.payload(0)))
+            .collect();
+        let paused = self
+            .paused
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|queued| with_progress(queued.download.payload(0)))
+            .collect();
+
+        Ok(DownloadsPayload {
+            active,
+            queued,
+            paused,
+        })
+    }
+
+    /// Changes the number of downloads running at the same time. Running downloads are not
+    /// interrupted when it is lowered, the next ones wait for a free slot.
+    pub fn set_max_concurrent_downloads(&self, value: u32) {
+        self.max_concurrent
+            .store(value.max(1) as usize, Ordering::Relaxed);
+        self.queue_notifier.notify_one();
+    }
+
+    /// Starts the queued downloads as long as there are free slots. Downloads of a game
+    /// that is already being downloaded wait for the running one to end.
     fn process_queue(&self) {
         let queue_clone = self.queue.clone();
         let queue_notifier = self.queue_notifier.clone();
+        let paused = self.paused.clone();
+        let active = self.active.clone();
+        let max_concurrent = self.max_concurrent.clone();
 
         tokio::spawn(async move {
             loop {
-                let download = {
+                let started = {
                     let mut queue_lock = queue_clone.lock().unwrap();
-                    queue_lock.pop_front()
-                };
-
-                if let Some(download) = download {
-                    let path = download.download_options.install_location.clone();
-                    let file_name = download.file_name.clone();
+                    let mut active_lock = active.lock().unwrap();
 
-                    let payload = DownloadPayload {
-                        game_id: download.game_id.clone(),
-                        game_source: download.game_source.clone(),
-                        game_title: download.game_title.clone(),
-                        download_size: download.download_size,
-                        downloaded: download.download_size,
+                    let index = if active_lock.len() < max_concurrent.load(Ordering::Relaxed) {
+                        queue_lock.iter().position(|queued| {
+                            !active_lock.contains_key(&(
+                                queued.download.game_source.clone(),
+                                queued.download.game_id.clone(),
+                            ))
+                        })
+                    } else {
+                        None
                     };
 
-                    Self::download(download).await;
-
-                    APP.get()
-                        .unwrap()
-                        .emit("download-finished", &payload)
-                        .unwrap();
-
-                    let result = get_storefront(&payload.game_source)
-                        .read()
-                        .await
-                        .post_download(&payload.game_id, path, &file_name)
-                        .await;
-
-                    APP.get()
-                        .unwrap()
-                        .emit("download-installed", &payload)
-                        .unwrap();
+                    index
+                        .and_then(|index| queue_lock.remove(index))
+                        .map(|queued| {
+                            let key = (
+                                queued.download.game_source.clone(),
+                                queued.download.game_id.clone(),
+                            );
+                            let (control_sender, control) = watch::channel(DownloadControl::Run);
+                            active_lock.insert(
+                                key,
+                                ActiveDownload {
+                                    control: control_sender,
+                                    payload: queued.download.payload(0),
+                                },
+                            );
+                            (queued, control)
+                        })
+                };
 
-                    if let Err(e) = result {
-                        println!("Error post-download: {}", e);
-                    }
+                if let Some((queued, control)) = started {
+                    tokio::spawn(Self::run_download(
+                        queued,
+                        control,
+                        paused.clone(),
+                        active.clone(),
+                        queue_notifier.clone(),
+                    ));
                 } else {
                     println!("Waiting for downloads...");
                     queue_notifier.notified().await;
@@ -124,94 +409,628 @@ impl DownloadManager {
         });
     }
 
-    async fn download(download: Download) {
-        let (writer_tx, mut writer_rx) = mpsc::channel(16);
-        let (verifier_tx, mut verifier_rx) = mpsc::channel(16);
+    /// Runs a download started by the queue and handles how it ended. The slot of a finished
+    /// download is freed before the game is installed, which can take a while.
+    async fn run_download(
+        mut queued: QueuedDownload,
+        control: watch::Receiver<DownloadControl>,
+        paused: Arc<Mutex<Vec<QueuedDownload>>>,
+        active: Arc<Mutex<HashMap<(GameSource, String), ActiveDownload>>>,
+        queue_notifier: Arc<Notify>,
+    ) {
+        let download = &queued.download;
+        let path = download.download_options.install_location.clone();
+        let file_names: Vec<String> = download
+            .files
+            .iter()
+            .map(|file| file.file_name.clone())
+            .collect();
 
-        let downloader = task::spawn(async move {
-            let mut response = download.request.send().await.unwrap();
+        let payload = download.payload(download.download_size);
 
-            while let Some(chunk) = response.chunk().await.unwrap() {
-                if (writer_tx.send(chunk).await).is_err() {
-                    break;
+        let outcome = Self::download(download, queued.resume, control).await;
+
+        let release_slot = || {
+            active
+                .lock()
+                .unwrap()
+                .remove(&(payload.game_source.clone(), payload.game_id.clone()));
+            queue_notifier.notify_one();
+        };
+
+        match outcome {
+            DownloadOutcome::Finished => release_slot(),
+            DownloadOutcome::Paused { downloaded } => {
+                if let Err(e) = save_pause(download, downloaded) {
+                    println!("Error saving download: {}", e);
                 }
+
+                emit_event("download-paused", download.payload(downloaded));
+
+                // Set aside before the slot is freed, so that resuming it never finds it missing
+                queued.resume = true;
+                paused.lock().unwrap().push(queued);
+                release_slot();
+                return;
             }
-        });
+            DownloadOutcome::Cancelled => {
+                if let Err(e) = discard_download(download, queued.previous_version.as_ref()).await {
+                    println!("Error cancelling download: {}", e);
+                }
+                release_slot();
+                emit_event("download-cancelled", download.payload(0));
+                return;
+            }
+            DownloadOutcome::Failed { reason } => {
+                println!("Download failed: {}", reason);
+                if let Err(e) = discard_download(download, queued.previous_version.as_ref()).await {
+                    println!("Error discarding download: {}", e);
+                }
+                release_slot();
+                emit_failed(download, reason);
+                return;
+            }
+        }
 
-        fs::create_dir_all(&download.download_options.install_location)
-            .await
-            .unwrap();
+        emit_event("download-finished", &payload);
 
-        let file_path = download
-            .download_options
-            .install_location
-            .join(&download.file_name);
+        let result = match move_into_place(download).await {
+            Ok(()) => {
+                get_storefront(&payload.game_source)
+                    .read()
+                    .await
+                    .post_download(&payload.game_id, queued.version.clone(), path, file_names)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
 
-        let mut file = OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(&file_path)
-            .await
-            .unwrap();
+        match result {
+            Ok(()) => emit_event("download-installed", &payload),
+            Err(e) => {
+                println!("Error post-download: {}", e);
+                if let Err(e) = revert_install(download, queued.previous_version.as_ref()).await {
+                    println!("Error reverting install: {}", e);
+                }
+                emit_failed(download, e.to_string());
+            }
+        }
+
+        if let Err(e) = database::create_connection().and_then(|mut connection| {
+            DownloadEntry::delete(&mut connection, &payload.game_source, &payload.game_id)
+        }) {
+            println!("Error removing download: {}", e);
+        }
+    }
 
-        let md5_exists = download.md5.is_some();
+    async fn download(
+        download: &Download,
+        resume: bool,
+        control: watch::Receiver<DownloadControl>,
+    ) -> DownloadOutcome {
         let total_written = Arc::new(AtomicU64::new(0));
         let total_written_clone = total_written.clone();
 
+        let payload = download.payload(0);
+
         let progress_reporter = task::spawn(async move {
             let mut interval = time::interval(Duration::from_secs(1));
-            let app_handle = APP.get().unwrap();
+            let mut connection = database::create_connection().ok();
             loop {
                 interval.tick().await;
                 let written = total_written.load(Ordering::Relaxed);
                 println!("Downloaded: {}", written);
-                app_handle
-                    .emit(
-                        "download-progress",
-                        DownloadPayload {
-                            game_id: download.game_id.clone(),
-                            game_source: download.game_source.clone(),
-                            game_title: download.game_title.clone(),
-                            download_size: download.download_size,
-                            downloaded: written,
-                        },
-                    )
-                    .unwrap();
+                if let Some(connection) = connection.as_mut() {
+                    let _ = DownloadEntry::update_progress(
+                        connection,
+                        &payload.game_source,
+                        &payload.game_id,
+                        written,
+                    );
+                }
+                emit_event(
+                    "download-progress",
+                    DownloadPayload {
+                        downloaded: written,
+                        ..payload.clone()
+                    },
+                );
+            }
+        });
+
+        let mut outcome = DownloadOutcome::Finished;
+
+        for file in &download.files {
+            let interruption = Self::download_file_with_retries(
+                file,
+                &download.download_options.install_location,
+                total_written_clone.clone(),
+                resume,
+                control.clone(),
+            )
+            .await;
+
+            match interruption {
+                Ok(Some(DownloadControl::Pause)) => {
+                    outcome = DownloadOutcome::Paused {
+                        downloaded: total_written_clone.load(Ordering::Relaxed),
+                    };
+                    break;
+                }
+                Ok(Some(DownloadControl::Cancel)) => {
+                    outcome = DownloadOutcome::Cancelled;
+                    break;
+                }
+                Ok(_) => (),
+                Err(e) => {
+                    outcome = DownloadOutcome::Failed {
+                        reason: e.to_string(),
+                    };
+                    break;
+                }
+            }
+        }
+
+        progress_reporter.abort();
+
+        outcome
+    }
+
+    /// Downloads a file, retrying with an exponential backoff when the error may be transient.
+    /// The retries continue from the partial file.
+    async fn download_file_with_retries(
+        file: &DownloadFile,
+        install_location: &Path,
+        total_written: Arc<AtomicU64>,
+        resume: bool,
+        mut control: watch::Receiver<DownloadControl>,
+    ) -> Result<Option<DownloadControl>> {
+        let written_before = total_written.load(Ordering::Relaxed);
+        let mut attempt = 0;
+
+        loop {
+            let result = Self::download_file(
+                file,
+                install_location,
+                total_written.clone(),
+                resume || attempt > 0,
+                control.clone(),
+            )
+            .await;
+
+            match result {
+                Err(e) if attempt + 1 < MAX_ATTEMPTS && is_transient(&e) => {
+                    let delay = RETRY_DELAY * 2u32.pow(attempt);
+                    println!(
+                        "Error downloading {}, retrying in {:?}: {}",
+                        file.file_name, delay, e
+                    );
+
+                    // The partial file is counted again when the download continues
+                    total_written.store(written_before, Ordering::Relaxed);
+                    attempt += 1;
+
+                    tokio::select! {
+                        _ = time::sleep(delay) => (),
+                        interruption = control.wait_for(|control| *control != DownloadControl::Run) => {
+                            return Ok(interruption.ok().map(|control| *control));
+                        }
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Downloads a file under its partial name, continuing from the partial file when resuming.
+    /// Returns the control that interrupted the download before the end of the file, if any.
+    async fn download_file(
+        file: &DownloadFile,
+        install_location: &Path,
+        total_written: Arc<AtomicU64>,
+        resume: bool,
+        mut control: watch::Receiver<DownloadControl>,
+    ) -> Result<Option<DownloadControl>> {
+        let (writer_tx, mut writer_rx) = mpsc::channel(16);
+        let (verifier_tx, mut verifier_rx) = mpsc::channel(16);
+
+        let file_path = partial_path(&install_location.join(&file.file_name));
+
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let partial_size = match fs::metadata(&file_path).await {
+            Ok(metadata) if resume => metadata.len(),
+            _ => 0,
+        };
+
+        let mut request = file
+            .request
+            .try_clone()
+            .ok_or("The download request can't be sent again")?;
+        if partial_size > 0 {
+            request = request.header(RANGE, format!("bytes={}-", partial_size));
+        }
+
+        let response = request.send().await?;
+
+        // The server answers that the range is not satisfiable when the file was already complete,
+        // and it may also ignore the range, in which case the file is downloaded again
+        let complete = response.status() == StatusCode::RANGE_NOT_SATISFIABLE;
+        let mut response = if complete {
+            response
+        } else {
+            response.error_for_status()?
+        };
+        let append = complete || response.status() == StatusCode::PARTIAL_CONTENT;
+
+        let mut output = OpenOptions::new()
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .write(true)
+            .open(&file_path)
+            .await?;
+
+        let md5_exists = file.md5.is_some();
+
+        // The hash continues from the data that was already downloaded
+        let mut hasher = md5::Context::new();
+        if append {
+            total_written.fetch_add(partial_size, Ordering::Relaxed);
+            if md5_exists {
+                hash_file(&file_path, &mut hasher).await?;
+            }
+        }
+
+        let downloader = task::spawn(async move {
+            if complete {
+                return Ok::<_, reqwest::Error>(None);
+            }
+
+            loop {
+                tokio::select! {
+                    chunk = response.chunk() => match chunk? {
+                        Some(chunk) => {
+                            // The writer stops on errors, which are returned by its task
+                            if (writer_tx.send(chunk).await).is_err() {
+                                return Ok(None);
+                            }
+                        }
+                        None => return Ok(None),
+                    },
+                    interruption = control.wait_for(|control| *control != DownloadControl::Run) => {
+                        return Ok(interruption.ok().map(|control| *control));
+                    }
+                }
             }
         });
 
         let writer = task::spawn(async move {
             while let Some(chunk) = writer_rx.recv().await {
-                file.write_all(&chunk).await.unwrap();
-                total_written_clone.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                output.write_all(&chunk).await?;
+                total_written.fetch_add(chunk.len() as u64, Ordering::Relaxed);
                 if md5_exists {
-                    verifier_tx.send(chunk).await.unwrap();
+                    let _ = verifier_tx.send(chunk).await;
                 }
             }
+            output.flush().await
         });
 
         let verifier = task::spawn(async move {
-            let mut hasher = md5::Context::new();
             while let Some(chunk) = verifier_rx.recv().await {
                 hasher.consume(&chunk);
             }
             hasher.compute()
         });
 
-        downloader.await.unwrap();
-        writer.await.unwrap();
+        // The writer must be done with the partial file before the download is retried
+        let interruption = downloader.await?;
+        writer.await??;
+        let interruption = interruption?;
 
-        progress_reporter.abort();
+        if interruption.is_some() {
+            println!("Interrupted: {}", file.file_name);
+            return Ok(interruption);
+        }
 
-        if let Some(md5) = download.md5 {
-            let result = verifier.await.unwrap();
+        if let Some(md5) = &file.md5 {
+            let result = verifier.await?;
             println!("MD5: {:x}", result);
-            if format!("{:x}", result) != md5 {
-                println!("MD5 mismatch!");
+            if &format!("{:x}", result) != md5 {
+                // The file is downloaded again from the start if it is retried
+                fs::remove_file(&file_path).await?;
+                return Err(Error::ChecksumMismatch(file.file_name.clone()));
             }
         }
 
-        println!("Downloaded: {}", download.file_name);
+        println!("Downloaded: {}", file.file_name);
+
+        Ok(None)
+    }
+}
+
+/// Whether an error may not happen again, e.g. a network issue or an overloaded server.
+fn is_transient(error: &Error) -> bool {
+    match error {
+        // The data may have been corrupted in transit
+        Error::ChecksumMismatch(_) => true,
+        Error::Reqwest(e) => match e.status() {
+            Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+            None => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+        },
+        _ => false,
+    }
+}
+
+/// Emits a download event. Failing to notify the frontend must not stop the downloads.
+fn emit_event<S>(event: &str, payload: S)
+where
+    S: Serialize + Clone,
+{
+    if let Err(e) = APP.get().unwrap().emit(event, payload) {
+        println!("Error emitting {}: {}", event, e);
     }
 }
+
+fn emit_failed(download: &Download, reason: String) {
+    emit_event(
+        "download-failed",
+        DownloadFailedPayload {
+            game_id: download.game_id.clone(),
+            game_source: download.game_source.clone(),
+            game_title: download.game_title.clone(),
+            reason,
+        },
+    );
+}
+
+fn save_download(download: &Download, previous_version: Option<&InstalledVersion>) -> Result<()> {
+    let mut connection = database::create_connection()?;
+
+    let previous_version = previous_version
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| e.to_string())?;
+
+    DownloadEntry {
+        game_id: download.game_id.clone(),
+        game_source: download.game_source.clone(),
+        version_id: download.version_id.clone(),
+        download_options: serde_json::to_string(&download.download_options)
+            .map_err(|e| e.to_string())?,
+        downloaded: 0,
+        paused: false,
+        into_install: download.download_options.install_location.exists(),
+        queued_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64,
+        previous_version,
+    }
+    .upsert(&mut connection)
+}
+
+fn save_pause(download: &Download, downloaded: u64) -> Result<()> {
+    let mut connection = database::create_connection()?;
+
+    DownloadEntry::update_progress(
+        &mut connection,
+        &download.game_source,
+        &download.game_id,
+        downloaded,
+    )?;
+    DownloadEntry::set_paused(
+        &mut connection,
+        &download.game_source,
+        &download.game_id,
+        true,
+    )
+}
+
+/// Rebuilds a stored download, by preparing the download of its version again.
+async fn rebuild_download(
+    connection: &mut SqliteConnection,
+    entry: &DownloadEntry,
+) -> Result<Option<QueuedDownload>> {
+    let Some(version_id) = entry.version_id.clone() else {
+        return Ok(None);
+    };
+
+    let download_options: DownloadOptions =
+        serde_json::from_str(&entry.download_options).map_err(|e| e.to_string())?;
+
+    let mut game = Game::select_one(connection, &entry.game_source, &entry.game_id)?;
+    let previous_version = stored_previous_version(entry, &game)?;
+
+    // The download is prepared again from the version it started from, e.g. for the patches
+    if let Some(previous_version) = &previous_version {
+        game.set_installed_version(previous_version.clone());
+    }
+
+    let download = get_storefront(&entry.game_source)
+        .read()
+        .await
+        .pre_download(&mut game, version_id, download_options)
+        .await?;
+
+    let Some(download) = download else {
+        return Ok(None);
+    };
+
+    let version = game.installed_version();
+
+    // The version is saved once installed, an existing install keeps its own until then
+    if let Some(previous_version) = &previous_version {
+        game.set_installed_version(previous_version.clone());
+    }
+    game.update(connection)?;
+
+    Ok(Some(QueuedDownload {
+        download,
+        // Downloads that never started have no partial files, only files of the install
+        resume: entry.downloaded > 0,
+        version,
+        previous_version,
+    }))
+}
+
+/// The version of a game before a stored download. Downloads stored without it, which go
+/// into an existing install, left the game at that version.
+fn stored_previous_version(entry: &DownloadEntry, game: &Game) -> Result<Option<InstalledVersion>> {
+    match &entry.previous_version {
+        Some(previous_version) => Ok(Some(
+            serde_json::from_str(previous_version).map_err(|e| e.to_string())?,
+        )),
+        None => Ok(entry.into_install.then(|| game.installed_version())),
+    }
+}
+
+/// Gives up on a stored download that can't be rebuilt. The partial files of a new install
+/// are removed with its directory, as it only contains what was downloaded.
+async fn abandon_download(connection: &mut SqliteConnection, entry: &DownloadEntry) -> Result<()> {
+    let game = Game::select_one(connection, &entry.game_source, &entry.game_id)?;
+    let previous_version = stored_previous_version(entry, &game)?;
+
+    if !entry.into_install {
+        if let Some(path) = game.path.filter(|path| Path::new(path).exists()) {
+            fs::remove_dir_all(path).await?;
+        }
+    }
+
+    restore_game(
+        connection,
+        &entry.game_source,
+        &entry.game_id,
+        previous_version.as_ref(),
+    )?;
+    DownloadEntry::delete(connection, &entry.game_source, &entry.game_id)
+}
+
+/// Restores a game after a download that will not complete: downloads into an existing
+/// install (e.g. updates) leave it installed at its previous version, the other ones uninstalled.
+fn restore_game(
+    connection: &mut SqliteConnection,
+    game_source: &GameSource,
+    game_id: &str,
+    previous_version: Option<&InstalledVersion>,
+) -> Result<()> {
+    let mut game = Game::select_one(connection, game_source, game_id)?;
+
+    match previous_version {
+        Some(previous_version) => {
+            game.status = GameStatus::Installed;
+            game.set_installed_version(previous_version.clone());
+        }
+        None => {
+            game.status = GameStatus::NotInstalled;
+            game.path = None;
+            game.set_installed_version(InstalledVersion::default());
+        }
+    }
+
+    game.update(connection)
+}
+
+/// Restores a game whose downloaded files could not be installed. An existing install is
+/// left at its previous version, a new one is removed.
+async fn revert_install(
+    download: &Download,
+    previous_version: Option<&InstalledVersion>,
+) -> Result<()> {
+    let install_location = &download.download_options.install_location;
+    if previous_version.is_none() {
+        if install_location.exists() {
+            fs::remove_dir_all(install_location).await?;
+        }
+    } else {
+        // Files that were not moved into place, e.g. after one failed to move
+        for file in &download.files {
+            let file_path = partial_path(&install_location.join(&file.file_name));
+            if file_path.exists() {
+                fs::remove_file(&file_path).await?;
+            }
+        }
+    }
+
+    let mut connection = database::create_connection()?;
+    restore_game(
+        &mut connection,
+        &download.game_source,
+        &download.game_id,
+        previous_version,
+    )
+}
+
+/// Removes the files of a download that will not complete, and restores the game.
+async fn discard_download(
+    download: &Download,
+    previous_version: Option<&InstalledVersion>,
+) -> Result<()> {
+    let install_location = &download.download_options.install_location;
+
+    for file in &download.files {
+        // Only the partial files are removed, the files of an existing install are left intact
+        let file_path = partial_path(&install_location.join(&file.file_name));
+        if file_path.exists() {
+            fs::remove_file(&file_path).await?;
+        }
+
+        // Directories only created for the download are empty, the other ones can't be removed
+        for dir in file_path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(install_location))
+        {
+            if fs::remove_dir(dir).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    let mut connection = database::create_connection()?;
+    restore_game(
+        &mut connection,
+        &download.game_source,
+        &download.game_id,
+        previous_version,
+    )?;
+    DownloadEntry::delete(&mut connection, &download.game_source, &download.game_id)
+}
+
+/// Path a file is downloaded to. Files only get their name once the whole download is
+/// complete, so that e.g. a cancelled update doesn't remove the files it would replace.
+fn partial_path(file_path: &Path) -> PathBuf {
+    let mut partial_path = file_path.as_os_str().to_owned();
+    partial_path.push(PARTIAL_EXTENSION);
+    PathBuf::from(partial_path)
+}
+
+/// Moves the files of a complete download from their partial names into place.
+async fn move_into_place(download: &Download) -> Result<()> {
+    let install_location = &download.download_options.install_location;
+
+    for file in &download.files {
+        let file_path = install_location.join(&file.file_name);
+        fs::rename(partial_path(&file_path), &file_path).await?;
+    }
+
+    Ok(())
+}
+
+/// Feeds the content of a partially downloaded file to the hasher.
+async fn hash_file(file_path: &Path, hasher: &mut md5::Context) -> Result<()> {
+    let mut file = File::open(file_path).await?;
+    let mut buffer = vec![0; 1024 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.consume(&buffer[..read]);
+    }
+
+    Ok(())
+}
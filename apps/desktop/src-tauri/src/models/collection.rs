@@ -0,0 +1,101 @@
+use super::game::{Game, GameSource};
+use crate::{
+    common::result::Result,
+    schema::{collection_games, collections},
+};
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// A named group of games curated by the user in a storefront.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug, Serialize)]
+#[diesel(table_name = crate::schema::collections)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub id: String,
+    pub source: GameSource,
+    pub title: String,
+}
+
+/// An entry of a collection. The game may not be owned by the user.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug, Serialize)]
+#[diesel(table_name = crate::schema::collection_games)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionGame {
+    pub collection_id: String,
+    pub source: GameSource,
+    pub game_id: String,
+    pub position: i32,
+    pub blurb: Option<String>,
+}
+
+/// A collection as fetched from a storefront, along with its entries and their games.
+pub struct FetchedCollection {
+    pub collection: Collection,
+    pub entries: Vec<CollectionGame>,
+    pub games: Vec<Game>,
+}
+
+impl Collection {
+    pub fn select(connection: &mut SqliteConnection) -> Result<Vec<Collection>> {
+        let results = collections::table
+            .order(collections::title.asc())
+            .load(connection)?;
+
+        Ok(results)
+    }
+
+    /// Replaces all the collections of a storefront with the fetched ones.
+    /// Not owned games are inserted so that they can be shown in their collections.
+    pub fn replace(
+        connection: &mut SqliteConnection,
+        source: &GameSource,
+        fetched_collections: Vec<FetchedCollection>,
+    ) -> Result<()> {
+        connection.transaction(|connection| {
+            diesel::delete(collection_games::table.filter(collection_games::source.eq(source)))
+                .execute(connection)?;
+            diesel::delete(collections::table.filter(collections::source.eq(source)))
+                .execute(connection)?;
+
+            for fetched in fetched_collections {
+                diesel::insert_into(collections::table)
+                    .values(&fetched.collection)
+                    .execute(connection)?;
+
+                if !fetched.entries.is_empty() {
+                    diesel::insert_or_ignore_into(collection_games::table)
+                        .values(&fetched.entries)
+                        .execute(connection)?;
+                }
+
+                if !fetched.games.is_empty() {
+                    diesel::insert_or_ignore_into(crate::schema::games::table)
+                        .values(&fetched.games)
+                        .execute(connection)?;
+                }
+            }
+
+            diesel::QueryResult::Ok(())
+        })?;
+
+        Ok(())
+    }
+}
+
+impl CollectionGame {
+    pub fn select(
+        connection: &mut SqliteConnection,
+        source: &GameSource,
+        collection_id: &str,
+    ) -> Result<Vec<CollectionGame>> {
+        let results = collection_games::table
+            .filter(collection_games::source.eq(source))
+            .filter(collection_games::collection_id.eq(collection_id))
+            .order(collection_games::position.asc())
+            .load(connection)?;
+
+        Ok(results)
+    }
+}
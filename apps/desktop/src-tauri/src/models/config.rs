@@ -1,6 +1,9 @@
 use crate::{common::result::Result, schema::configs::dsl::*};
 use diesel::prelude::*;
 
+/// Number of downloads running at the same time, unless changed in the settings.
+const DEFAULT_MAX_CONCURRENT_DOWNLOADS: i32 = 2;
+
 #[derive(Queryable, Selectable, Insertable, AsChangeset, Clone, Debug, Default)]
 #[diesel(table_name = crate::schema::configs)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
@@ -10,6 +13,12 @@ pub struct Config {
     legacy_games_token: Option<String>,
     legacy_games_email: Option<String>,
     epic_games_refresh_token: Option<String>,
+    wine_runner_path: Option<String>,
+    show_incompatible_versions: bool,
+    itchio_synced_at: Option<i64>,
+    itchio_full_synced_at: Option<i64>,
+    media_location: Option<String>,
+    max_concurrent_downloads: i32,
 }
 
 impl Config {
@@ -33,7 +42,10 @@ impl Config {
     }
 
     fn new(connection: &mut SqliteConnection) -> Result<Config> {
-        let config = Config::default();
+        let config = Config {
+            max_concurrent_downloads: DEFAULT_MAX_CONCURRENT_DOWNLOADS,
+            ..Default::default()
+        };
 
         diesel::insert_into(configs)
             .values(&config)
@@ -97,4 +109,92 @@ impl Config {
         self.update(connection)?;
         Ok(())
     }
+
+    pub fn wine_runner_path(&self) -> Option<String> {
+        self.wine_runner_path.clone()
+    }
+
+    pub fn set_wine_runner_path(
+        &mut self,
+        value: Option<String>,
+        connection: &mut SqliteConnection,
+    ) -> Result<()> {
+        self.wine_runner_path = value;
+        self.update(connection)?;
+        Ok(())
+    }
+
+    pub fn show_incompatible_versions(&self) -> bool {
+        self.show_incompatible_versions
+    }
+
+    pub fn set_show_incompatible_versions(
+        &mut self,
+        value: bool,
+        connection: &mut SqliteConnection,
+    ) -> Result<()> {
+        self.show_incompatible_versions = value;
+        self.update(connection)?;
+        Ok(())
+    }
+
+    /// Directory where media, such as soundtracks or books, is downloaded to.
+    pub fn media_location(&self) -> Option<String> {
+        self.media_location.clone()
+    }
+
+    pub fn set_media_location(
+        &mut self,
+        value: Option<String>,
+        connection: &mut SqliteConnection,
+    ) -> Result<()> {
+        self.media_location = value;
+        self.update(connection)?;
+        Ok(())
+    }
+
+    /// Number of downloads that can run at the same time, at least one.
+    pub fn max_concurrent_downloads(&self) -> u32 {
+        self.max_concurrent_downloads.max(1) as u32
+    }
+
+    pub fn set_max_concurrent_downloads(
+        &mut self,
+        value: u32,
+        connection: &mut SqliteConnection,
+    ) -> Result<()> {
+        self.max_concurrent_downloads = value.max(1) as i32;
+        self.update(connection)?;
+        Ok(())
+    }
+
+    /// Unix timestamp of the most recently updated itch.io key seen in the last library sync.
+    pub fn itchio_synced_at(&self) -> Option<i64> {
+        self.itchio_synced_at
+    }
+
+    pub fn set_itchio_synced_at(
+        &mut self,
+        value: Option<i64>,
+        connection: &mut SqliteConnection,
+    ) -> Result<()> {
+        self.itchio_synced_at = value;
+        self.update(connection)?;
+        Ok(())
+    }
+
+    /// Unix timestamp of the last itch.io library sync that went through every key.
+    pub fn itchio_full_synced_at(&self) -> Option<i64> {
+        self.itchio_full_synced_at
+    }
+
+    pub fn set_itchio_full_synced_at(
+        &mut self,
+        value: Option<i64>,
+        connection: &mut SqliteConnection,
+    ) -> Result<()> {
+        self.itchio_full_synced_at = value;
+        self.update(connection)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,91 @@
+use super::game::GameSource;
+use crate::{common::result::Result, schema::downloads::dsl::*};
+use diesel::prelude::*;
+
+/// A download of the queue, stored so that the queue can be restored after a restart.
+/// The requests can't be stored, they are rebuilt from the version to download.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::schema::downloads)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct DownloadEntry {
+    pub game_id: String,
+    pub game_source: GameSource,
+    /// Version to download, or nothing when the download can't be rebuilt (e.g. a repair).
+    pub version_id: Option<String>,
+    /// The download options, as JSON.
+    pub download_options: String,
+    pub downloaded: i64,
+    pub paused: bool,
+    /// Whether the download goes into an existing install, e.g. an update.
+    pub into_install: bool,
+    pub queued_at: i64,
+    /// Version of the game before a download into an existing install, as JSON.
+    /// The download is rebuilt from it, as the game only gets the new version once installed.
+    pub previous_version: Option<String>,
+}
+
+impl DownloadEntry {
+    /// Selects the downloads in the order they were queued.
+    pub fn select(connection: &mut SqliteConnection) -> Result<Vec<DownloadEntry>> {
+        let entries = downloads.order(queued_at.asc()).load(connection)?;
+
+        Ok(entries)
+    }
+
+    pub fn upsert(&self, connection: &mut SqliteConnection) -> Result<()> {
+        diesel::replace_into(downloads)
+            .values(self)
+            .execute(connection)?;
+
+        Ok(())
+    }
+
+    pub fn update_progress(
+        connection: &mut SqliteConnection,
+        source: &GameSource,
+        game: &str,
+        value: u64,
+    ) -> Result<()> {
+        diesel::update(
+            downloads
+                .filter(game_source.eq(source))
+                .filter(game_id.eq(game)),
+        )
+        .set(downloaded.eq(value as i64))
+        .execute(connection)?;
+
+        Ok(())
+    }
+
+    pub fn set_paused(
+        connection: &mut SqliteConnection,
+        source: &GameSource,
+        game: &str,
+        value: bool,
+    ) -> Result<()> {
+        diesel::update(
+            downloads
+                .filter(game_source.eq(source))
+                .filter(game_id.eq(game)),
+        )
+        .set(paused.eq(value))
+        .execute(connection)?;
+
+        Ok(())
+    }
+
+    pub fn delete(
+        connection: &mut SqliteConnection,
+        source: &GameSource,
+        game: &str,
+    ) -> Result<()> {
+        diesel::delete(
+            downloads
+                .filter(game_source.eq(source))
+                .filter(game_id.eq(game)),
+        )
+        .execute(connection)?;
+
+        Ok(())
+    }
+}
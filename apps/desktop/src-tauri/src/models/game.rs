@@ -1,16 +1,21 @@
-use std::path::Path;
+use std::{collections::HashSet, path::Path};
 
 use crate::{common::result::Result, schema::games::dsl::*};
 use diesel::prelude::*;
 use diesel_derive_enum::DbEnum;
 use serde::{Deserialize, Serialize};
-use strum_macros::EnumIter;
+use strum_macros::{Display, EnumIter, EnumString};
 
 use super::payloads::GameFiltersPayload;
 
+/// Number of values bound in a single query, below the limit of older SQLite versions.
+const MAX_QUERY_PARAMETERS: usize = 900;
+
 #[derive(Queryable, Selectable, Insertable, AsChangeset, Clone, Debug, Serialize)]
 #[diesel(table_name = crate::schema::games)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+// Updates write the whole row, so that fields can be reset, e.g. the version on uninstall
+#[diesel(treat_none_as_null = true)]
 #[serde(rename_all = "camelCase")]
 pub struct Game {
     pub id: String,
@@ -26,6 +31,13 @@ pub struct Game {
     pub hidden: bool,
     pub cover_url: Option<String>,
     pub sort_title: String,
+    pub version_id: Option<String>,
+    pub channel: Option<String>,
+    pub owned: bool,
+    pub store_url: Option<String>,
+    /// Platform of the installed version. Games for other platforms are launched through Wine.
+    pub platform: Option<GamePlatform>,
+    pub classification: GameClassification,
 }
 
 impl Game {
@@ -42,6 +54,15 @@ impl Game {
         Ok(game)
     }
 
+    /// Selects the games that are installed, whether they are up to date or not.
+    pub fn select_installed(connection: &mut SqliteConnection) -> Result<Vec<Game>> {
+        let installed_games = games
+            .filter(status.eq_any([GameStatus::Installed, GameStatus::UpdateAvailable]))
+            .load(connection)?;
+
+        Ok(installed_games)
+    }
+
     pub fn update(&self, connection: &mut SqliteConnection) -> Result<()> {
         diesel::update(games.filter(id.eq(&self.id)))
             .set(self)
@@ -58,11 +79,187 @@ impl Game {
         Ok(())
     }
 
+    /// Marks the given games as owned, in case they were previously added
+    /// as not owned entries of a collection.
+    pub fn mark_owned(connection: &mut SqliteConnection, values: &[Game]) -> Result<()> {
+        connection.transaction(|connection| {
+            for game in values {
+                diesel::update(
+                    games
+                        .filter(source.eq(&game.source))
+                        .filter(id.eq(&game.id))
+                        .filter(owned.eq(false)),
+                )
+                .set((owned.eq(true), key.eq(&game.key)))
+                .execute(connection)?;
+            }
+
+            diesel::QueryResult::Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Updates the classification of the given games, in case it changed since they were added.
+    pub fn update_classifications(
+        connection: &mut SqliteConnection,
+        values: &[Game],
+    ) -> Result<()> {
+        connection.transaction(|connection| {
+            for game in values {
+                diesel::update(
+                    games
+                        .filter(source.eq(&game.source))
+                        .filter(id.eq(&game.id))
+                        .filter(classification.ne(&game.classification)),
+                )
+                .set(classification.eq(&game.classification))
+                .execute(connection)?;
+            }
+
+            diesel::QueryResult::Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Marks the games of a source that are not in the given list as not owned,
+    /// e.g. because their key was revoked or refunded.
+    pub fn mark_not_owned(
+        connection: &mut SqliteConnection,
+        game_source: &GameSource,
+        values: &[Game],
+    ) -> Result<()> {
+        let owned_ids: HashSet<&str> = values.iter().map(|game| game.id.as_str()).collect();
+
+        // Compared here rather than in the query, as a library can hold more games than
+        // SQLite accepts parameters
+        let stored_ids: Vec<String> = games
+            .filter(source.eq(game_source))
+            .filter(owned.eq(true))
+            .select(id)
+            .load(connection)?;
+        let not_owned_ids: Vec<String> = stored_ids
+            .into_iter()
+            .filter(|stored_id| !owned_ids.contains(stored_id.as_str()))
+            .collect();
+
+        for chunk in not_owned_ids.chunks(MAX_QUERY_PARAMETERS) {
+            diesel::update(
+                games
+                    .filter(source.eq(game_source))
+                    .filter(id.eq_any(chunk)),
+            )
+            .set((owned.eq(false), key.eq(None::<String>)))
+            .execute(connection)?;
+        }
+
+        Ok(())
+    }
+
+    /// Merges a duplicate entry of a game into the given game, e.g. when a game is owned
+    /// through two entitlements. The install status, launch targets and favorite of the
+    /// duplicate are kept, then the duplicate is removed.
+    /// Returns whether the install of the duplicate was moved to the game.
+    pub fn merge_duplicate(
+        connection: &mut SqliteConnection,
+        duplicate_id: &str,
+        game: &Game,
+    ) -> Result<bool> {
+        use crate::schema::launch_targets;
+
+        let moved_install = connection.transaction(|connection| {
+            let Some(duplicate) = games
+                .filter(source.eq(&game.source))
+                .filter(id.eq(duplicate_id))
+                .first::<Game>(connection)
+                .optional()?
+            else {
+                return diesel::QueryResult::Ok(false);
+            };
+
+            let mut merged = games
+                .filter(source.eq(&game.source))
+                .filter(id.eq(&game.id))
+                .first::<Game>(connection)
+                .optional()?
+                .unwrap_or_else(|| Game {
+                    favorite: duplicate.favorite,
+                    hidden: duplicate.hidden,
+                    ..game.clone()
+                });
+
+            merged.favorite |= duplicate.favorite;
+            merged.hidden &= duplicate.hidden;
+
+            let keep_install = merged.status == GameStatus::NotInstalled
+                && duplicate.status != GameStatus::NotInstalled;
+
+            if keep_install {
+                merged.launch_target = duplicate.launch_target;
+                merged.path = duplicate.path;
+                merged.version = duplicate.version;
+                merged.status = duplicate.status;
+                merged.version_id = duplicate.version_id;
+                merged.channel = duplicate.channel;
+                merged.platform = duplicate.platform;
+            }
+
+            diesel::replace_into(games)
+                .values(&merged)
+                .execute(connection)?;
+
+            diesel::delete(
+                games
+                    .filter(source.eq(&game.source))
+                    .filter(id.eq(duplicate_id)),
+            )
+            .execute(connection)?;
+
+            let duplicate_targets = launch_targets::table
+                .filter(launch_targets::game_source.eq(&game.source))
+                .filter(launch_targets::game_id.eq(duplicate_id));
+
+            if keep_install {
+                diesel::delete(
+                    launch_targets::table
+                        .filter(launch_targets::game_source.eq(&game.source))
+                        .filter(launch_targets::game_id.eq(&game.id)),
+                )
+                .execute(connection)?;
+
+                diesel::update(duplicate_targets)
+                    .set(launch_targets::game_id.eq(&game.id))
+                    .execute(connection)?;
+            } else {
+                diesel::delete(duplicate_targets).execute(connection)?;
+            }
+
+            diesel::QueryResult::Ok(keep_install)
+        })?;
+
+        Ok(moved_install)
+    }
+
+    pub fn installed_version(&self) -> InstalledVersion {
+        InstalledVersion {
+            version: self.version.clone(),
+            version_id: self.version_id.clone(),
+            channel: self.channel.clone(),
+            platform: self.platform.clone(),
+        }
+    }
+
+    pub fn set_installed_version(&mut self, installed_version: InstalledVersion) {
+        self.version = installed_version.version;
+        self.version_id = installed_version.version_id;
+        self.channel = installed_version.channel;
+        self.platform = installed_version.platform;
+    }
+
     /// Refreshes the status of installed games in case they were manually removed.
     pub fn refresh_installed(connection: &mut SqliteConnection) -> Result<()> {
-        let installed_games = games
-            .filter(status.eq(GameStatus::Installed))
-            .load::<Game>(connection)?;
+        let installed_games = Self::select_installed(connection)?;
 
         for mut game in installed_games {
             if let Some(game_path) = &game.path {
@@ -72,6 +269,10 @@ impl Game {
             }
 
             game.status = GameStatus::NotInstalled;
+            game.version = None;
+            game.version_id = None;
+            game.channel = None;
+            game.platform = None;
             game.update(connection)?;
         }
 
@@ -94,6 +295,9 @@ pub struct ReducedGame {
     pub favorite: bool,
     pub hidden: bool,
     pub cover_url: Option<String>,
+    pub owned: bool,
+    pub store_url: Option<String>,
+    pub classification: GameClassification,
 }
 
 impl ReducedGame {
@@ -103,7 +307,11 @@ impl ReducedGame {
     ) -> Result<Vec<ReducedGame>> {
         let mut statement = games.select(ReducedGame::as_select()).into_boxed();
 
-        statement = statement.filter(hidden.eq(false)).order(sort_title.asc());
+        // Installed games stay in the library even if they are no longer owned
+        statement = statement
+            .filter(hidden.eq(false))
+            .filter(owned.eq(true).or(status.ne(GameStatus::NotInstalled)))
+            .order(sort_title.asc());
 
         if let Some(filters) = filters {
             if let Some(query) = filters.query {
@@ -111,20 +319,107 @@ impl ReducedGame {
                     statement = statement.filter(title.like(format!("%{}%", query)));
                 }
             }
+
+            if let Some(classifications) = filters.classifications {
+                statement = statement.filter(classification.eq_any(classifications));
+            }
         }
 
         let results: Vec<ReducedGame> = statement.load(connection).unwrap();
 
         Ok(results)
     }
+
+    /// Selects the games in a collection, whether they are owned or not.
+    pub fn select_in_collection(
+        connection: &mut SqliteConnection,
+        collection_source: &GameSource,
+        collection: &str,
+    ) -> Result<Vec<ReducedGame>> {
+        use crate::schema::collection_games;
+
+        let game_ids = collection_games::table
+            .filter(collection_games::source.eq(collection_source))
+            .filter(collection_games::collection_id.eq(collection))
+            .select(collection_games::game_id);
+
+        let results = games
+            .select(ReducedGame::as_select())
+            .filter(source.eq(collection_source))
+            .filter(id.eq_any(game_ids))
+            .filter(hidden.eq(false))
+            .load(connection)?;
+
+        Ok(results)
+    }
 }
 
-#[derive(Serialize, Clone, Debug)]
+/// How a browser (HTML5) game has to be embedded, as the storefront describes it.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::schema::game_embeds)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct GameEmbed {
+    pub game_id: String,
+    pub game_source: GameSource,
+    pub width: i32,
+    pub height: i32,
+    pub fullscreen: bool,
+}
+
+impl GameEmbed {
+    pub fn select_one(
+        connection: &mut SqliteConnection,
+        source: &GameSource,
+        game: &str,
+    ) -> Result<Option<GameEmbed>> {
+        use crate::schema::game_embeds;
+
+        let embed = game_embeds::table
+            .filter(game_embeds::game_source.eq(source))
+            .filter(game_embeds::game_id.eq(game))
+            .first(connection)
+            .optional()?;
+
+        Ok(embed)
+    }
+
+    pub fn upsert(&self, connection: &mut SqliteConnection) -> Result<()> {
+        use crate::schema::game_embeds;
+
+        diesel::replace_into(game_embeds::table)
+            .values(self)
+            .execute(connection)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct GameVersion {
     pub id: String,
     pub name: String,
     pub external: bool,
+    /// Release channel of the version, e.g. "windows-beta" on itch.io.
+    pub channel: Option<String>,
+    /// Kind of content, e.g. "default" or "soundtrack".
+    pub version_type: Option<String>,
+    pub download_size: Option<u64>,
+    pub demo: bool,
+    pub preorder: bool,
+    /// Version of the build, as shown by the developer.
+    pub build_version: Option<String>,
+    /// Whether the version is for another platform and needs e.g. Wine to run.
+    pub requires_compatibility_layer: bool,
+}
+
+/// Result of checking the files of an installed game.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GameVerification {
+    pub healthy: bool,
+    /// Problems found in the install, such as corrupted or missing files.
+    pub issues: Vec<String>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -134,14 +429,73 @@ pub struct GameVersionInfo {
     pub download_size: u64,
 }
 
-#[derive(DbEnum, Serialize, Deserialize, Clone, Debug, PartialEq, EnumIter)]
+/// The fields of a game describing its installed version. A download sets them once the game
+/// is installed, and they are restored if it doesn't complete.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
+pub struct InstalledVersion {
+    pub version: Option<String>,
+    pub version_id: Option<String>,
+    pub channel: Option<String>,
+    pub platform: Option<GamePlatform>,
+}
+
+#[derive(
+    DbEnum, Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, EnumIter, Display, EnumString,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
 pub enum GameSource {
     Itchio,
     LegacyGames,
     EpicGames,
 }
 
+#[derive(DbEnum, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum GameClassification {
+    Game,
+    Tool,
+    Assets,
+    GameMod,
+    PhysicalGame,
+    Soundtrack,
+    Other,
+    Comic,
+    Book,
+}
+
+impl GameClassification {
+    /// Whether the item can be launched, as opposed to media like soundtracks or books,
+    /// which are opened with the default application of the system.
+    pub fn is_launchable(&self) -> bool {
+        matches!(self, Self::Game | Self::Tool)
+    }
+}
+
+#[derive(DbEnum, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum GamePlatform {
+    Windows,
+    Linux,
+    Macos,
+    Web,
+}
+
+impl GamePlatform {
+    /// The platform Fusion is running on.
+    pub fn native() -> Self {
+        #[cfg(target_os = "windows")]
+        let platform = Self::Windows;
+        #[cfg(target_os = "linux")]
+        let platform = Self::Linux;
+        #[cfg(target_os = "macos")]
+        let platform = Self::Macos;
+
+        platform
+    }
+}
+
 #[derive(DbEnum, Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum GameStatus {
@@ -150,4 +504,5 @@ pub enum GameStatus {
     Downloading,
     Installing,
     Uninstalling,
+    UpdateAvailable,
 }
@@ -0,0 +1,49 @@
+use crate::{common::result::Result, schema::legacy_games_install_sizes::dsl::*};
+use diesel::prelude::*;
+
+/// Installed size of a Legacy Games installer. The size is estimated from the API until
+/// the installer is downloaded, then it is computed from the contents of the archive.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::schema::legacy_games_install_sizes)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct InstallSize {
+    pub installer_uuid: String,
+    pub install_size: i64,
+    pub estimated: bool,
+}
+
+impl InstallSize {
+    pub fn select_one(
+        connection: &mut SqliteConnection,
+        uuid: &str,
+    ) -> Result<Option<InstallSize>> {
+        let size = legacy_games_install_sizes
+            .filter(installer_uuid.eq(uuid))
+            .first(connection)
+            .optional()?;
+
+        Ok(size)
+    }
+
+    /// Stores the estimated sizes, without replacing the ones already known.
+    pub fn insert_estimates(
+        connection: &mut SqliteConnection,
+        values: &[InstallSize],
+    ) -> Result<()> {
+        if !values.is_empty() {
+            diesel::insert_or_ignore_into(legacy_games_install_sizes)
+                .values(values)
+                .execute(connection)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn upsert(&self, connection: &mut SqliteConnection) -> Result<()> {
+        diesel::replace_into(legacy_games_install_sizes)
+            .values(self)
+            .execute(connection)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use super::game::GameSource;
+use crate::{common::result::Result, schema::launch_targets::dsl::*};
+use diesel::prelude::*;
+
+/// A candidate launch target of an installed game, so the user can pick one when there are several.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::schema::launch_targets)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct LaunchTarget {
+    pub game_id: String,
+    pub game_source: GameSource,
+    pub path: String,
+}
+
+impl LaunchTarget {
+    pub fn select(
+        connection: &mut SqliteConnection,
+        source: &GameSource,
+        game: &str,
+    ) -> Result<Vec<LaunchTarget>> {
+        let targets = launch_targets
+            .filter(game_source.eq(source))
+            .filter(game_id.eq(game))
+            .load(connection)?;
+
+        Ok(targets)
+    }
+
+    /// Replaces the launch targets of a game with the given paths.
+    pub fn replace(
+        connection: &mut SqliteConnection,
+        source: &GameSource,
+        game: &str,
+        paths: &[String],
+    ) -> Result<()> {
+        Self::delete(connection, source, game)?;
+
+        let values: Vec<LaunchTarget> = paths
+            .iter()
+            .map(|target_path| LaunchTarget {
+                game_id: game.to_string(),
+                game_source: source.clone(),
+                path: target_path.clone(),
+            })
+            .collect();
+
+        if !values.is_empty() {
+            diesel::insert_or_ignore_into(launch_targets)
+                .values(&values)
+                .execute(connection)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn delete(
+        connection: &mut SqliteConnection,
+        source: &GameSource,
+        game: &str,
+    ) -> Result<()> {
+        diesel::delete(
+            launch_targets
+                .filter(game_source.eq(source))
+                .filter(game_id.eq(game)),
+        )
+        .execute(connection)?;
+
+        Ok(())
+    }
+}
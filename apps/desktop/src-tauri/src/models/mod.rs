@@ -1,3 +1,7 @@
+pub mod collection;
 pub mod config;
+pub mod download;
 pub mod game;
+pub mod install_size;
+pub mod launch_target;
 pub mod payloads;
@@ -1,4 +1,4 @@
-use super::game::GameSource;
+use super::game::{GameClassification, GameSource, ReducedGame};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Clone, Debug)]
@@ -11,8 +11,62 @@ pub struct DownloadPayload {
     pub downloaded: u64,
 }
 
+/// The downloads of the manager, for the frontend to catch up with the events it missed.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadsPayload {
+    pub active: Vec<DownloadPayload>,
+    pub queued: Vec<DownloadPayload>,
+    pub paused: Vec<DownloadPayload>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadFailedPayload {
+    pub game_id: String,
+    pub game_source: GameSource,
+    pub game_title: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsPayload {
+    pub wine_runner_path: Option<String>,
+    pub show_incompatible_versions: bool,
+    pub media_location: Option<String>,
+    pub max_concurrent_downloads: u32,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct GameFiltersPayload {
     pub query: Option<String>,
+    /// Only include these kinds of items, e.g. to separate games from soundtracks and books.
+    pub classifications: Option<Vec<GameClassification>>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum ItchioLoginPayload {
+    Success,
+    #[serde(rename_all = "camelCase")]
+    TotpNeeded {
+        token: String,
+    },
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum LegacyGamesLoginPayload {
+    Success,
+    PasswordNeeded,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionGamePayload {
+    pub game: ReducedGame,
+    pub position: i32,
+    pub blurb: Option<String>,
 }
@@ -1,3 +1,21 @@
+diesel::table! {
+    collection_games (collection_id, source, game_id) {
+        collection_id -> Text,
+        source -> crate::models::game::GameSourceMapping,
+        game_id -> Text,
+        position -> Integer,
+        blurb -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    collections (id, source) {
+        id -> Text,
+        source -> crate::models::game::GameSourceMapping,
+        title -> Text,
+    }
+}
+
 diesel::table! {
     configs (id) {
         id -> Integer,
@@ -5,6 +23,36 @@ diesel::table! {
         legacy_games_token -> Nullable<Text>,
         legacy_games_email -> Nullable<Text>,
         epic_games_refresh_token -> Nullable<Text>,
+        wine_runner_path -> Nullable<Text>,
+        show_incompatible_versions -> Bool,
+        itchio_synced_at -> Nullable<BigInt>,
+        itchio_full_synced_at -> Nullable<BigInt>,
+        media_location -> Nullable<Text>,
+        max_concurrent_downloads -> Integer,
+    }
+}
+
+diesel::table! {
+    downloads (game_id, game_source) {
+        game_id -> Text,
+        game_source -> crate::models::game::GameSourceMapping,
+        version_id -> Nullable<Text>,
+        download_options -> Text,
+        downloaded -> BigInt,
+        paused -> Bool,
+        into_install -> Bool,
+        queued_at -> BigInt,
+        previous_version -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    game_embeds (game_id, game_source) {
+        game_id -> Text,
+        game_source -> crate::models::game::GameSourceMapping,
+        width -> Integer,
+        height -> Integer,
+        fullscreen -> Bool,
     }
 }
 
@@ -23,5 +71,29 @@ diesel::table! {
         hidden -> Bool,
         cover_url -> Nullable<Text>,
         sort_title -> Text,
+        version_id -> Nullable<Text>,
+        channel -> Nullable<Text>,
+        owned -> Bool,
+        store_url -> Nullable<Text>,
+        platform -> Nullable<crate::models::game::GamePlatformMapping>,
+        classification -> crate::models::game::GameClassificationMapping,
+    }
+}
+
+diesel::table! {
+    launch_targets (game_id, game_source, path) {
+        game_id -> Text,
+        game_source -> crate::models::game::GameSourceMapping,
+        path -> Text,
     }
 }
+
+diesel::table! {
+    legacy_games_install_sizes (installer_uuid) {
+        installer_uuid -> Text,
+        install_size -> BigInt,
+        estimated -> Bool,
+    }
+}
+
+diesel::allow_tables_to_appear_in_same_query!(collection_games, games);
@@ -4,7 +4,10 @@ use crate::{
     managers::download::{Download, DownloadOptions},
     models::{
         config::Config,
-        game::{Game, GameSource, GameStatus, GameVersion, GameVersionInfo},
+        game::{
+            Game, GameClassification, GameSource, GameStatus, GameVersion, GameVersionInfo,
+            InstalledVersion,
+        },
     },
     APP,
 };
@@ -79,6 +82,12 @@ impl Storefront for EpicGames {
                         .find(|image| image.image_type == KeyImageType::DieselGameBoxTall)
                         .map(|image| image.url.clone()),
                     sort_title: game.title.to_lowercase(),
+                    version_id: None,
+                    channel: None,
+                    owned: true,
+                    store_url: None,
+                    platform: None,
+                    classification: GameClassification::Game,
                 })
                 .collect(),
         ))
@@ -101,6 +110,7 @@ impl Storefront for EpicGames {
                 id: v.clone(),
                 name: v,
                 external: false,
+                ..Default::default()
             })
             .collect())
     }
@@ -157,7 +167,13 @@ impl Storefront for EpicGames {
         Ok(())
     }
 
-    async fn post_download(&self, game_id: &str, path: PathBuf, file_name: &str) -> Result<()> {
+    async fn post_download(
+        &self,
+        game_id: &str,
+        version: InstalledVersion,
+        path: PathBuf,
+        file_names: Vec<String>,
+    ) -> Result<()> {
         Ok(())
     }
 }
@@ -1,29 +1,82 @@
 use super::storefront::Storefront;
 use crate::{
     common::{database, result::Result},
-    managers::download::{Download, DownloadOptions},
+    managers::download::{Download, DownloadFile, DownloadManager, DownloadOptions},
     models::{
+        collection::{Collection, CollectionGame, FetchedCollection},
         config::Config,
-        game::{Game, GameSource, GameStatus, GameVersion, GameVersionInfo},
-        payloads::DownloadPayload,
+        game::{
+            Game, GameClassification, GameEmbed, GamePlatform, GameSource, GameStatus,
+            GameVerification, GameVersion, GameVersionInfo, InstalledVersion,
+        },
+        launch_target::LaunchTarget,
+        payloads::DownloadFailedPayload,
     },
     util, APP,
 };
 use async_trait::async_trait;
+use diesel::{Connection, QueryableByName, RunQueryDsl, SqliteConnection};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
 use std::{
+    io::Read,
     path::{Path, PathBuf},
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tauri::{
+    webview::DownloadEvent, Emitter, Manager, Url, WebviewUrl, WebviewWindow, WindowEvent,
 };
-use tauri::{webview::DownloadEvent, Emitter, Manager, Url, WebviewUrl, WebviewWindow};
 use tokio::fs;
 use wrapper_itchio::{
-    api::models::{UploadStorage, UploadTraits},
+    api::models::{
+        BuildFileType, Game as ItchioGame, GameClassification as ItchioGameClassification, Upload,
+        UploadStorage, UploadTraits, UploadType,
+    },
     ItchioClient,
 };
 
+/// How often the library sync goes through every key instead of only the recently updated ones.
+const FULL_SYNC_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Directory, relative to the install location, where wharf patches are downloaded to.
+const PATCHES_DIR: &str = ".patches";
+
+/// Directory, relative to the install location, where the files to verify and heal a build are downloaded to.
+const HEAL_DIR: &str = ".heal";
+
+/// Flavors of the itch.io launch targets that can run on this platform.
+#[cfg(target_os = "linux")]
+const LAUNCH_TARGET_FLAVORS: &[&str] = &["linux", "script", "html"];
+#[cfg(target_os = "windows")]
+const LAUNCH_TARGET_FLAVORS: &[&str] = &["windows", "windows-script", "html"];
+#[cfg(target_os = "macos")]
+const LAUNCH_TARGET_FLAVORS: &[&str] = &["app-macos", "macos", "script", "html"];
+
+/// Architectures of the itch.io launch targets that can run on this platform, preferred first.
+#[cfg(target_arch = "x86_64")]
+const LAUNCH_TARGET_ARCHS: &[&str] = &["amd64", "386"];
+#[cfg(target_arch = "x86")]
+const LAUNCH_TARGET_ARCHS: &[&str] = &["386"];
+#[cfg(target_arch = "aarch64")]
+const LAUNCH_TARGET_ARCHS: &[&str] = &["arm64"];
+#[cfg(not(any(target_arch = "x86_64", target_arch = "x86", target_arch = "aarch64")))]
+const LAUNCH_TARGET_ARCHS: &[&str] = &[];
+
 #[derive(Default)]
 pub struct Itchio {
     client: Option<Arc<ItchioClient>>,
+    /// State of the last fetch, saved once the fetched games are stored.
+    pending_sync: Mutex<Option<PendingSync>>,
+}
+
+struct PendingSync {
+    synced_at: Option<i64>,
+    /// Time of a sync that went through every key, and the games it found.
+    full_sync: Option<(i64, Vec<Game>)>,
 }
 
 #[async_trait]
@@ -37,13 +90,8 @@ impl Storefront for Itchio {
             .unwrap()
             .itchio_api_key();
 
-        if api_key.is_none() {
-            return Ok(());
-        };
-
-        let client = ItchioClient::new(api_key.unwrap());
-
-        self.client = Some(Arc::new(client));
+        // The storefront may be re-initialized after logging in or out
+        self.client = api_key.map(|api_key| Arc::new(ItchioClient::new(api_key)));
 
         Ok(())
     }
@@ -54,46 +102,145 @@ impl Storefront for Itchio {
             None => return Ok(None),
         };
 
+        let (synced_at, full_synced_at) = {
+            let config = APP.get().unwrap().state::<RwLock<Config>>();
+            let config = config.read().unwrap();
+            (config.itchio_synced_at(), config.itchio_full_synced_at())
+        };
+
+        // Every so often go through all keys, to find the ones that were revoked or refunded
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let full_sync = full_synced_at.is_none_or(|full_synced_at| {
+            now - full_synced_at > FULL_SYNC_INTERVAL.as_secs() as i64
+        });
+        let mut cursor = if full_sync { None } else { synced_at };
+
         let mut games = Vec::new();
-        let mut page = 1;
+        let mut latest_updated_at = synced_at;
+        let mut previous_updated_at = None;
+        let mut page: u32 = 1;
 
         loop {
             let owned_keys = client.fetch_owned_keys(page).await?;
-            let current_page_count = owned_keys.owned_keys.len() as u8;
+            let current_page_count = owned_keys.owned_keys.len() as u32;
 
-            games.extend(owned_keys.owned_keys.into_iter().map(|key| {
-                let developer = key
-                    .game
-                    .user
-                    .and_then(|user| user.display_name.or(Some(user.username)));
+            let updated_at: Vec<i64> = owned_keys
+                .owned_keys
+                .iter()
+                .map(|key| key.updated_at.and_utc().timestamp())
+                .collect();
 
-                Game {
-                    id: key.game.id.to_string(),
-                    title: key.game.title.clone(),
-                    source: GameSource::Itchio,
-                    key: Some(key.id.to_string()),
-                    developer,
-                    launch_target: None,
-                    path: None,
-                    version: None,
-                    status: GameStatus::NotInstalled,
-                    favorite: false,
-                    hidden: false,
-                    cover_url: key.game.still_cover_url.or(key.game.cover_url),
-                    sort_title: key.game.title.to_lowercase(),
+            latest_updated_at = updated_at.iter().copied().chain(latest_updated_at).max();
+
+            // Stopping early relies on the most recently updated keys coming first, so
+            // go through every key if they don't
+            for updated_at in &updated_at {
+                if previous_updated_at.is_some_and(|previous| *updated_at > previous) {
+                    cursor = None;
                 }
-            }));
+                previous_updated_at = Some(*updated_at);
+            }
+
+            // Keys that were not updated since the last sync are already in the library
+            let reached_cursor = cursor.is_some_and(|cursor| {
+                !updated_at.is_empty() && updated_at.iter().all(|updated_at| *updated_at < cursor)
+            });
 
-            if current_page_count < owned_keys.per_page {
+            games.extend(
+                owned_keys
+                    .owned_keys
+                    .into_iter()
+                    .map(|key| create_game(key.game, Some(key.id.to_string()))),
+            );
+
+            if current_page_count < owned_keys.per_page || reached_cursor {
                 break;
             }
 
             page += 1;
         }
 
+        *self.pending_sync.lock().unwrap() = Some(PendingSync {
+            synced_at: latest_updated_at,
+            full_sync: cursor.is_none().then(|| (now, games.clone())),
+        });
+
         Ok(Some(games))
     }
 
+    async fn finish_sync(&self) -> Result<()> {
+        let Some(pending_sync) = self.pending_sync.lock().unwrap().take() else {
+            return Ok(());
+        };
+
+        let mut connection = database::create_connection()?;
+        let config = APP.get().unwrap().state::<RwLock<Config>>();
+        let mut config = config.write().unwrap();
+
+        if let Some((full_synced_at, games)) = pending_sync.full_sync {
+            Game::mark_not_owned(&mut connection, &GameSource::Itchio, &games)?;
+            config.set_itchio_full_synced_at(Some(full_synced_at), &mut connection)?;
+        }
+
+        config.set_itchio_synced_at(pending_sync.synced_at, &mut connection)?;
+
+        Ok(())
+    }
+
+    async fn fetch_collections(&self) -> Result<Option<Vec<FetchedCollection>>> {
+        let client = match &self.client {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        let mut fetched_collections = Vec::new();
+
+        for collection in client.fetch_collections().await? {
+            let mut entries = Vec::new();
+            let mut games = Vec::new();
+            let mut page = 1;
+
+            loop {
+                let collection_games = client.fetch_collection_games(collection.id, page).await?;
+                let current_page_count = collection_games.collection_games.len();
+
+                for collection_game in collection_games.collection_games {
+                    entries.push(CollectionGame {
+                        collection_id: collection.id.to_string(),
+                        source: GameSource::Itchio,
+                        game_id: collection_game.game.id.to_string(),
+                        position: collection_game.position as i32,
+                        blurb: collection_game.blurb,
+                    });
+                    games.push(create_game(collection_game.game, None));
+                }
+
+                if current_page_count == 0
+                    || current_page_count < collection_games.per_page as usize
+                {
+                    break;
+                }
+
+                page += 1;
+            }
+
+            fetched_collections.push(FetchedCollection {
+                collection: Collection {
+                    id: collection.id.to_string(),
+                    source: GameSource::Itchio,
+                    title: collection.title,
+                },
+                entries,
+                games,
+            });
+        }
+
+        Ok(Some(fetched_collections))
+    }
+
     async fn fetch_game_versions(&self, game: Game) -> Result<Vec<GameVersion>> {
         let client = match &self.client {
             Some(c) => c,
@@ -104,21 +251,48 @@ impl Storefront for Itchio {
         let game_key: u32 = game.key.unwrap().parse()?;
         let uploads = client.fetch_game_uploads(game_id, game_key).await?;
 
-        #[cfg(target_os = "linux")]
-        let os_trait = UploadTraits::PLinux;
-        #[cfg(target_os = "windows")]
-        let os_trait = UploadTraits::PWindows;
-        #[cfg(target_os = "macos")]
-        let os_trait = UploadTraits::POsx;
+        let show_incompatible_versions = APP
+            .get()
+            .unwrap()
+            .state::<RwLock<Config>>()
+            .read()
+            .unwrap()
+            .show_incompatible_versions();
+
+        // Media, like soundtracks or books, can be downloaded on any platform
+        let launchable = game.classification.is_launchable();
 
         let game_versions = uploads
             .into_iter()
-            .filter(|upload| upload.traits.contains(&os_trait))
+            .filter(|upload| {
+                !launchable
+                    || match upload_platform(upload) {
+                        Some(platform) if platform == GamePlatform::native() => true,
+                        // Browser games have no platform trait, as they run anywhere
+                        Some(GamePlatform::Web) => true,
+                        Some(GamePlatform::Windows) => show_incompatible_versions,
+                        _ => false,
+                    }
+            })
             .map(|upload| GameVersion {
+                requires_compatibility_layer: launchable
+                    && upload_platform(&upload)
+                        .is_some_and(|platform| platform == GamePlatform::Windows)
+                    && GamePlatform::native() != GamePlatform::Windows,
                 id: upload.id.to_string(),
-                name: upload.display_name.unwrap_or(upload.filename),
-                //download_size: upload.size.unwrap_or(0),
                 external: upload.storage == UploadStorage::External,
+                version_type: Some(upload.r#type.as_str().to_string()),
+                download_size: upload.size.map(u64::from),
+                demo: upload.traits.contains(&UploadTraits::Demo),
+                preorder: upload.traits.contains(&UploadTraits::Preorder),
+                build_version: upload.build.as_ref().map(|build| {
+                    build
+                        .user_version
+                        .clone()
+                        .unwrap_or_else(|| build.version.to_string())
+                }),
+                name: upload.display_name.unwrap_or(upload.filename),
+                channel: upload.channel_name,
             })
             .collect();
 
@@ -183,50 +357,315 @@ impl Storefront for Itchio {
 
         let upload = client.fetch_game_upload(upload_id, game_key).await?;
 
+        if let Some(download) =
+            pre_download_patches(client, game, &upload, game_key, &download_options).await?
+        {
+            return Ok(Some(download));
+        }
+
         let download_request = client.fetch_upload_download_url(upload_id, game_key);
 
         if upload.storage == UploadStorage::External {
             let response = download_request.send().await?;
             let url = response.url().to_owned();
+
+            // The game gets the version once the download started from the page is installed
+            let version = InstalledVersion {
+                version: Some(upload_version(&upload)),
+                version_id: Some(version_id),
+                channel: upload.channel_name.clone(),
+                platform: upload_platform(&upload),
+            };
+
             handle_external_download(
                 url,
-                &download_options.install_location,
+                download_options,
                 &game.id,
                 &game.title,
+                upload.size.unwrap_or(0) as u64,
+                version,
             )
             .await?;
             return Ok(None);
         }
 
-        game.version = upload
-            .build
-            .as_ref()
-            .map(|build| build.version.to_string())
-            .or(upload.md5_hash.clone());
+        game.version = Some(upload_version(&upload));
+        game.version_id = Some(version_id);
+        game.channel = upload.channel_name.clone();
+        game.platform = upload_platform(&upload);
+
+        if upload.r#type == UploadType::Html {
+            save_game_embed(client, game).await?;
+        }
 
         Ok(Some(Download {
-            request: download_request,
-            file_name: upload.filename,
+            files: vec![DownloadFile {
+                request: download_request,
+                file_name: upload.filename,
+                md5: upload.md5_hash,
+            }],
             download_options,
             game_source: GameSource::Itchio,
             game_id: game.id.clone(),
             game_title: game.title.clone(),
-            md5: upload.md5_hash,
             download_size: upload.size.unwrap_or(0) as u64,
+            version_id: game.version_id.clone(),
+        }))
+    }
+
+    async fn install_from_file(
+        &self,
+        game: &mut Game,
+        file_path: PathBuf,
+        install_location: PathBuf,
+    ) -> Result<()> {
+        let client = match &self.client {
+            Some(c) => c,
+            None => return Err("itch.io client is not initialized".into()),
+        };
+
+        let game_id: u32 = game.id.parse()?;
+        let game_key: u32 = game.key.clone().ok_or("The game is not owned")?.parse()?;
+
+        let uploads = client.fetch_game_uploads(game_id, game_key).await?;
+        let upload = match_upload(uploads, &file_path)
+            .await?
+            .ok_or("The file does not match any upload of this game")?;
+
+        // The archive is removed once extracted, so keep the original file intact
+        let copied_file = install_location.join(&upload.filename);
+        fs::create_dir_all(&install_location).await?;
+        fs::copy(&file_path, &copied_file).await?;
+
+        game.version = Some(upload_version(&upload));
+        game.version_id = Some(upload.id.to_string());
+        game.channel = upload.channel_name.clone();
+        game.platform = upload_platform(&upload);
+        game.path = Some(install_location.to_string_lossy().into_owned());
+        game.status = GameStatus::Installing;
+
+        let mut connection = database::create_connection()?;
+        game.update(&mut connection)?;
+
+        if upload.r#type == UploadType::Html {
+            save_game_embed(client, game).await?;
+        }
+
+        let result = post_download(
+            Some(client),
+            &game.id,
+            game.installed_version(),
+            install_location,
+            &[upload.filename],
+        )
+        .await;
+
+        // The install location may hold a previous install, so only the copy is removed here
+        if result.is_err() && fs::try_exists(&copied_file).await.unwrap_or(false) {
+            fs::remove_file(&copied_file).await?;
+        }
+
+        result
+    }
+
+    async fn verify_game(&self, game: &Game) -> Result<GameVerification> {
+        let client = match &self.client {
+            Some(c) => c,
+            None => return Err("itch.io client is not initialized".into()),
+        };
+
+        let (Some(path), Some(version_id), Some(key)) = (&game.path, &game.version_id, &game.key)
+        else {
+            return Err("The game is not installed".into());
+        };
+
+        if !util::butler::is_available() {
+            return Err(util::butler::NOT_FOUND.into());
+        }
+
+        let upload_id: u32 = version_id.parse()?;
+        let game_key: u32 = key.parse()?;
+
+        let build_id = find_installed_build_id(client, game, upload_id, game_key).await?;
+        let build = client.fetch_upload_build(build_id, game_key).await?;
+        let signature_file = build
+            .file(BuildFileType::Signature)
+            .ok_or("The installed build has no signature")?;
+
+        let path = PathBuf::from(path);
+        let heal_dir = path.join(HEAL_DIR);
+        let signature_path = heal_dir.join(format!("{}.pws", build_id));
+
+        let signature = client
+            .fetch_build_download_url(
+                build_id,
+                &BuildFileType::Signature,
+                &signature_file.sub_type,
+                game_key,
+            )
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        fs::create_dir_all(&heal_dir).await?;
+        fs::write(&signature_path, signature).await?;
+
+        let issues = util::butler::check(&signature_path, &path).await;
+
+        fs::remove_dir_all(&heal_dir).await?;
+
+        let issues = issues?;
+
+        Ok(GameVerification {
+            healthy: issues.is_empty(),
+            issues,
+        })
+    }
+
+    async fn pre_repair(&self, game: &mut Game) -> Result<Option<Download>> {
+        let client = match &self.client {
+            Some(c) => c,
+            None => return Err("itch.io client is not initialized".into()),
+        };
+
+        let (Some(path), Some(version_id), Some(key)) =
+            (game.path.clone(), game.version_id.clone(), game.key.clone())
+        else {
+            return Err("The game is not installed".into());
+        };
+
+        if !util::butler::is_available() {
+            return Err(util::butler::NOT_FOUND.into());
+        }
+
+        let upload_id: u32 = version_id.parse()?;
+        let game_key: u32 = key.parse()?;
+
+        let download_options = DownloadOptions {
+            install_location: PathBuf::from(path),
+        };
+
+        // If there is a newer build, patching to it also repairs the game, as the result is verified
+        let upload = client.fetch_game_upload(upload_id, game_key).await?;
+        if let Ok(Some(download)) =
+            pre_download_patches(client, game, &upload, game_key, &download_options).await
+        {
+            return Ok(Some(download));
+        }
+
+        let build_id = find_installed_build_id(client, game, upload_id, game_key).await?;
+        let build = client.fetch_upload_build(build_id, game_key).await?;
+
+        let (Some(archive_file), Some(signature_file)) = (
+            build.file(BuildFileType::Archive),
+            build.file(BuildFileType::Signature),
+        ) else {
+            return Err("The installed build has no archive to repair the game with".into());
+        };
+
+        let files = vec![
+            DownloadFile {
+                request: client.fetch_build_download_url(
+                    build_id,
+                    &BuildFileType::Archive,
+                    &archive_file.sub_type,
+                    game_key,
+                ),
+                file_name: format!("{}/{}.zip", HEAL_DIR, build_id),
+                md5: None,
+            },
+            DownloadFile {
+                request: client.fetch_build_download_url(
+                    build_id,
+                    &BuildFileType::Signature,
+                    &signature_file.sub_type,
+                    game_key,
+                ),
+                file_name: format!("{}/{}.pws", HEAL_DIR, build_id),
+                md5: None,
+            },
+        ];
+
+        Ok(Some(Download {
+            files,
+            download_options,
+            game_source: GameSource::Itchio,
+            game_id: game.id.clone(),
+            game_title: game.title.clone(),
+            download_size: archive_file.size as u64 + signature_file.size as u64,
+            version_id: None,
         }))
     }
 
     async fn launch_game(&self, game: Game) -> Result<()> {
+        if game
+            .launch_target
+            .as_ref()
+            .is_some_and(|target| target.ends_with(".html"))
+        {
+            let mut connection = database::create_connection()?;
+            let embed = GameEmbed::select_one(&mut connection, &GameSource::Itchio, &game.id)?;
+            return util::html::open_game_window(&game, embed);
+        }
+
         let game_path = game.path.unwrap();
         let launch_target = game.launch_target.unwrap();
 
         let target_path = PathBuf::from(&game_path).join(&launch_target);
 
-        util::file::execute_file(&target_path)?;
+        match game.platform {
+            Some(GamePlatform::Windows) if GamePlatform::native() != GamePlatform::Windows => {
+                util::wine::execute_file(&target_path, None)?
+            }
+            _ => util::file::execute_file(&target_path)?,
+        }
 
         Ok(())
     }
 
+    async fn fetch_game_update(&self, game: &Game) -> Result<Option<String>> {
+        let client = match &self.client {
+            Some(c) => c,
+            None => return Err("itch.io client is not initialized".into()),
+        };
+
+        let (Some(version_id), Some(key)) = (&game.version_id, &game.key) else {
+            return Ok(None);
+        };
+
+        let upload_id: u32 = version_id.parse()?;
+        let game_key: u32 = key.parse()?;
+
+        let upload = match client.fetch_game_upload(upload_id, game_key).await {
+            Ok(upload) => upload,
+            // The upload may have been replaced by a new one in the same channel
+            Err(_) => {
+                let Some(channel) = &game.channel else {
+                    return Ok(None);
+                };
+
+                let uploads = client
+                    .fetch_game_uploads(game.id.parse()?, game_key)
+                    .await?;
+                match uploads
+                    .into_iter()
+                    .find(|upload| upload.channel_name.as_ref() == Some(channel))
+                {
+                    Some(upload) => upload,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        if upload.id != upload_id || Some(upload_version(&upload)) != game.version {
+            return Ok(Some(upload.id.to_string()));
+        }
+
+        Ok(None)
+    }
+
     async fn uninstall_game(&self, game: &Game) -> Result<()> {
         let path = PathBuf::from(game.path.as_ref().unwrap());
 
@@ -237,161 +676,585 @@ impl Storefront for Itchio {
         Ok(())
     }
 
-    async fn post_download(&self, game_id: &str, path: PathBuf, file_name: &str) -> Result<()> {
-        post_download(game_id, path, file_name).await
+    async fn post_download(
+        &self,
+        game_id: &str,
+        version: InstalledVersion,
+        path: PathBuf,
+        file_names: Vec<String>,
+    ) -> Result<()> {
+        post_download(self.client.as_deref(), game_id, version, path, &file_names).await
     }
 }
 
-async fn post_download(game_id: &str, path: PathBuf, file_name: &str) -> Result<()> {
-    let file_path = path.join(file_name);
+/// Finds the upload a local file was downloaded from, by its file name, size or MD5 hash.
+async fn match_upload(uploads: Vec<Upload>, file_path: &Path) -> Result<Option<Upload>> {
+    let file_name = file_path
+        .file_name()
+        .ok_or("Invalid file path")?
+        .to_string_lossy()
+        .into_owned();
+    let size = fs::metadata(file_path).await?.len();
+
+    let mut md5 = None;
+    let mut verified = Vec::new();
+
+    // Only uploads whose size and hash (when itch.io provides them) match the file are kept
+    for upload in uploads {
+        if upload.size.is_some_and(|s| u64::from(s) != size) {
+            continue;
+        }
 
-    let mut connection = database::create_connection()?;
-    let mut game = Game::select_one(&mut connection, &GameSource::Itchio, game_id)?;
+        match &upload.md5_hash {
+            Some(hash) => {
+                if md5.is_none() {
+                    md5 = Some(util::file::md5_file(file_path).await?);
+                }
+                if md5.as_ref() == Some(hash) {
+                    verified.push(upload);
+                }
+            }
+            // An upload with neither a size nor a hash can't be verified
+            None if upload.size.is_some() => verified.push(upload),
+            None => {}
+        }
+    }
 
-    if file_path.extension().unwrap() == "zip"
-        || file_path.extension().unwrap() == "7z"
-        || file_path.extension().unwrap() == "rar"
+    // Browsers may rename duplicate downloads, e.g. "game (1).zip", so the name is only a hint
+    if let Some(index) = verified
+        .iter()
+        .position(|upload| upload.filename.eq_ignore_ascii_case(&file_name))
     {
-        println!("Extracting game: {:?}", file_path);
-        util::file::extract_file(&file_path, &path).await?;
+        return Ok(Some(verified.swap_remove(index)));
     }
 
-    let mut launch_target = util::fs::find_launch_target(&path).await?;
+    if verified.len() == 1 {
+        return Ok(verified.pop());
+    }
 
-    println!("Launch target: {:?}", launch_target);
+    // Several uploads share the size, only a matching hash tells them apart
+    Ok(verified
+        .into_iter()
+        .find(|upload| upload.md5_hash.is_some()))
+}
 
-    // Strip base path from launch target
-    if let Some(target) = &launch_target {
-        #[cfg(unix)]
-        util::file::set_permissions(&target, 0o755).await?;
-        launch_target = Some(target.strip_prefix(&path).unwrap().to_path_buf());
+/// Determines the platform an upload is made for, preferring the one Fusion runs on.
+fn upload_platform(upload: &Upload) -> Option<GamePlatform> {
+    if upload.r#type == UploadType::Html {
+        return Some(GamePlatform::Web);
     }
 
-    game.launch_target = launch_target.map(|target| target.to_string_lossy().into_owned());
-    game.status = GameStatus::Installed;
-    game.update(&mut connection).unwrap();
+    let platforms = [
+        (UploadTraits::PWindows, GamePlatform::Windows),
+        (UploadTraits::PLinux, GamePlatform::Linux),
+        (UploadTraits::POsx, GamePlatform::Macos),
+    ];
+
+    let mut platforms: Vec<GamePlatform> = platforms
+        .into_iter()
+        .filter(|(platform_trait, _)| upload.traits.contains(platform_trait))
+        .map(|(_, platform)| platform)
+        .collect();
+
+    match platforms.iter().position(|p| *p == GamePlatform::native()) {
+        Some(native) => Some(platforms.remove(native)),
+        None => platforms.into_iter().next(),
+    }
+}
+
+/// Flavors of the itch.io launch targets that can run the installed game.
+fn launch_target_flavors(game: &Game) -> &'static [&'static str] {
+    match game.platform {
+        Some(GamePlatform::Windows) => &["windows", "windows-script"],
+        _ => LAUNCH_TARGET_FLAVORS,
+    }
+}
+
+/// Stores how a browser game has to be embedded, so it can be played offline.
+async fn save_game_embed(client: &ItchioClient, game: &Game) -> Result<()> {
+    let itchio_game = client.fetch_game(game.id.parse()?).await?;
+
+    let embed = match itchio_game.embed {
+        Some(embed) => GameEmbed {
+            game_id: game.id.clone(),
+            game_source: GameSource::Itchio,
+            width: embed.width as i32,
+            height: embed.height as i32,
+            fullscreen: embed.fullscreen,
+        },
+        None => return Ok(()),
+    };
+
+    let mut connection = database::create_connection()?;
+    embed.upsert(&mut connection)?;
 
     Ok(())
 }
 
-async fn post_download_external(
+/// Creates a library entry from an itch.io game.
+/// Games without a download key are not owned, e.g. those only in a collection.
+fn create_game(game: ItchioGame, key: Option<String>) -> Game {
+    let developer = game
+        .user
+        .and_then(|user| user.display_name.or(Some(user.username)));
+
+    Game {
+        id: game.id.to_string(),
+        title: game.title.clone(),
+        source: GameSource::Itchio,
+        owned: key.is_some(),
+        key,
+        developer,
+        launch_target: None,
+        path: None,
+        version: None,
+        status: GameStatus::NotInstalled,
+        favorite: false,
+        hidden: false,
+        cover_url: game.still_cover_url.or(game.cover_url),
+        sort_title: game.title.to_lowercase(),
+        version_id: None,
+        channel: None,
+        store_url: Some(game.url),
+        platform: None,
+        classification: match game.classification {
+            ItchioGameClassification::Game => GameClassification::Game,
+            ItchioGameClassification::Tool => GameClassification::Tool,
+            ItchioGameClassification::Assets => GameClassification::Assets,
+            ItchioGameClassification::GameMod => GameClassification::GameMod,
+            ItchioGameClassification::PhysicalGame => GameClassification::PhysicalGame,
+            ItchioGameClassification::Soundtrack => GameClassification::Soundtrack,
+            ItchioGameClassification::Other => GameClassification::Other,
+            ItchioGameClassification::Comic => GameClassification::Comic,
+            ItchioGameClassification::Book => GameClassification::Book,
+        },
+    }
+}
+
+/// Fetches the launch targets that itch.io found when scanning the installed upload,
+/// keeping only the ones that exist and can run on this platform, best ones first.
+async fn fetch_launch_targets(client: &ItchioClient, game: &Game, path: &Path) -> Vec<String> {
+    let (Some(version_id), Some(key)) = (&game.version_id, &game.key) else {
+        return Vec::new();
+    };
+
+    let (Ok(upload_id), Ok(game_key)) = (version_id.parse::<u32>(), key.parse::<u32>()) else {
+        return Vec::new();
+    };
+
+    let scanned_archive = match client
+        .fetch_upload_scanned_archive(upload_id, game_key)
+        .await
+    {
+        Ok(scanned_archive) => scanned_archive,
+        Err(e) => {
+            println!("Error fetching launch targets: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut launch_targets: Vec<_> = scanned_archive
+        .launch_targets
+        .into_iter()
+        .filter(|target| launch_target_flavors(game).contains(&target.flavor.as_str()))
+        .filter(|target| {
+            target.arch.is_empty() || LAUNCH_TARGET_ARCHS.contains(&target.arch.as_str())
+        })
+        // macOS apps are bundles, i.e. directories
+        .filter(|target| {
+            let target_path = path.join(&target.path);
+            target_path.is_file() || (target.flavor == "app-macos" && target_path.is_dir())
+        })
+        .collect();
+
+    // Prefer the native architecture. Targets without one (e.g. scripts) go last.
+    launch_targets.sort_by_key(|target| {
+        LAUNCH_TARGET_ARCHS
+            .iter()
+            .position(|arch| *arch == target.arch)
+            .unwrap_or(LAUNCH_TARGET_ARCHS.len())
+    });
+
+    launch_targets
+        .into_iter()
+        .map(|target| target.path)
+        .collect()
+}
+
+/// The version of an upload: the build version for wharf builds, or else its MD5 hash
+/// or, as a last resort, the date it was last updated.
+fn upload_version(upload: &Upload) -> String {
+    upload
+        .build
+        .as_ref()
+        .map(|build| build.version.to_string())
+        .or(upload.md5_hash.clone())
+        .unwrap_or_else(|| upload.updated_at.to_string())
+}
+
+/// If the game has an older build of the same upload installed, builds a download
+/// with the chain of wharf patches needed to update it to the upload's latest build,
+/// plus the signature of the latest build to verify the result.
+/// Returns `None` if the game can't be patched and a full download is needed instead.
+async fn pre_download_patches(
+    client: &ItchioClient,
+    game: &mut Game,
+    upload: &Upload,
+    game_key: u32,
+    download_options: &DownloadOptions,
+) -> Result<Option<Download>> {
+    let installed_version = game
+        .version
+        .as_ref()
+        .and_then(|version| version.parse::<u32>().ok());
+
+    let (Some(target_build_id), Some(installed_version)) = (upload.build_id, installed_version)
+    else {
+        return Ok(None);
+    };
+
+    if !util::butler::is_available() {
+        return Ok(None);
+    }
+
+    if game.version_id != Some(upload.id.to_string()) || !download_options.install_location.exists()
+    {
+        return Ok(None);
+    }
+
+    let Some(installed_build_id) =
+        find_build_id(client, upload.id, installed_version, game_key).await?
+    else {
+        return Ok(None);
+    };
+
+    if installed_build_id == target_build_id {
+        return Err("The game is already up to date".into());
+    }
+
+    // Walk the build chain backwards, from the latest build to the installed one.
+    let mut files = Vec::new();
+    let mut signature = None;
+    let mut download_size = 0;
+    let mut build_id = target_build_id;
+
+    while build_id > installed_build_id {
+        let build = client.fetch_upload_build(build_id, game_key).await?;
+
+        if build.id == target_build_id {
+            let Some(signature_file) = build.file(BuildFileType::Signature) else {
+                return Ok(None);
+            };
+
+            download_size += signature_file.size as u64;
+            signature = Some(DownloadFile {
+                request: client.fetch_build_download_url(
+                    build.id,
+                    &BuildFileType::Signature,
+                    &signature_file.sub_type,
+                    game_key,
+                ),
+                file_name: format!("{}/{}.pws", PATCHES_DIR, build.id),
+                md5: None,
+            });
+        }
+
+        let Some(patch_file) = build.file(BuildFileType::Patch) else {
+            return Ok(None);
+        };
+
+        download_size += patch_file.size as u64;
+        files.push(DownloadFile {
+            request: client.fetch_build_download_url(
+                build.id,
+                &BuildFileType::Patch,
+                &patch_file.sub_type,
+                game_key,
+            ),
+            file_name: format!("{}/{}.pwr", PATCHES_DIR, build.id),
+            md5: None,
+        });
+
+        build_id = build.parent_build_id;
+    }
+
+    if build_id != installed_build_id {
+        return Ok(None);
+    }
+
+    // Patches must be applied from the oldest build to the newest one.
+    files.reverse();
+    files.extend(signature);
+
+    game.version = Some(upload_version(upload));
+
+    Ok(Some(Download {
+        files,
+        download_options: download_options.clone(),
+        game_source: GameSource::Itchio,
+        game_id: game.id.clone(),
+        game_title: game.title.clone(),
+        download_size,
+        version_id: Some(upload.id.to_string()),
+    }))
+}
+
+/// Finds the id of the build of an upload with the given version.
+async fn find_build_id(
+    client: &ItchioClient,
+    upload_id: u32,
+    version: u32,
+    game_key: u32,
+) -> Result<Option<u32>> {
+    let builds = client.fetch_upload_builds(upload_id, game_key).await?;
+
+    Ok(builds
+        .builds
+        .iter()
+        .find(|build| build.version == version)
+        .map(|build| build.id))
+}
+
+/// Finds the id of the installed build of a game, if it was published through butler.
+async fn find_installed_build_id(
+    client: &ItchioClient,
+    game: &Game,
+    upload_id: u32,
+    game_key: u32,
+) -> Result<u32> {
+    let installed_version = game
+        .version
+        .as_ref()
+        .and_then(|version| version.parse::<u32>().ok());
+
+    let build_id = match installed_version {
+        Some(installed_version) => {
+            find_build_id(client, upload_id, installed_version, game_key).await?
+        }
+        None => None,
+    };
+
+    build_id.ok_or_else(|| {
+        "The installed version was not published with butler, so it cannot be verified".into()
+    })
+}
+
+/// Repairs an install with a fresh archive of the installed build.
+async fn heal_install(path: &Path, file_names: &[String]) -> Result<()> {
+    let heal_dir = path.join(HEAL_DIR);
+
+    let result: Result<()> = async {
+        let signature = file_names
+            .iter()
+            .find(|name| name.ends_with(".pws"))
+            .ok_or("No signature to heal the game with")?;
+        let archive = file_names
+            .iter()
+            .find(|name| name.ends_with(".zip"))
+            .ok_or("No archive to heal the game with")?;
+
+        println!("Healing game: {:?}", path);
+        util::butler::heal(
+            &path.join(signature),
+            &path.to_path_buf(),
+            &path.join(archive),
+        )
+        .await
+    }
+    .await;
+
+    fs::remove_dir_all(&heal_dir).await?;
+
+    result
+}
+
+/// Applies the downloaded wharf patches in order and verifies the result against
+/// the signature of the latest build. The patches are removed afterwards.
+async fn apply_patches(path: &Path, file_names: &[String]) -> Result<()> {
+    let patches_dir = path.join(PATCHES_DIR);
+    let staging_dir = patches_dir.join("staging");
+
+    let result: Result<()> = async {
+        for file_name in file_names.iter().filter(|name| name.ends_with(".pwr")) {
+            println!("Applying patch: {}", file_name);
+            util::butler::apply_patch(&path.join(file_name), &path.to_path_buf(), &staging_dir)
+                .await?;
+        }
+
+        if let Some(signature) = file_names.iter().find(|name| name.ends_with(".pws")) {
+            util::butler::verify(&path.join(signature), &path.to_path_buf()).await?;
+        }
+
+        Ok(())
+    }
+    .await;
+
+    fs::remove_dir_all(&patches_dir).await?;
+
+    result
+}
+
+/// Installs the downloaded files. The version is saved with the launch targets, so that
+/// an update that fails to apply is still offered.
+async fn post_download(
+    client: Option<&ItchioClient>,
     game_id: &str,
+    version: InstalledVersion,
     path: PathBuf,
-    file_name: &str,
-    success: bool,
+    file_names: &[String],
 ) -> Result<()> {
-    if !success {
-        // TODO: handle download failure
-        return Err("Download failed".into());
+    let file_path = path.join(&file_names[0]);
+
+    let mut connection = database::create_connection()?;
+    let mut game = Game::select_one(&mut connection, &GameSource::Itchio, game_id)?;
+    // Only used in memory until the game is installed, e.g. for the platform of the launch target
+    game.set_installed_version(version);
+
+    if file_names.iter().any(|name| name.starts_with(HEAL_DIR)) {
+        heal_install(&path, file_names).await?;
+    } else if file_names.iter().any(|name| name.ends_with(".pwr")) {
+        apply_patches(&path, file_names).await?;
+    } else if file_path.extension().unwrap() == "zip"
+        || file_path.extension().unwrap() == "7z"
+        || file_path.extension().unwrap() == "rar"
+    {
+        println!("Extracting game: {:?}", file_path);
+        util::file::extract_file(&file_path, &path).await?;
     }
 
-    let file_path = path.join(file_name);
-    let size = fs::metadata(&file_path).await?.len();
+    install_launch_targets(client, &mut game, &path, &mut connection).await
+}
 
-    let mut connection = database::create_connection()?;
-    let game = Game::select_one(&mut connection, &GameSource::Itchio, game_id)?;
+/// Finds the launch targets of an installed game and marks it as installed.
+async fn install_launch_targets(
+    client: Option<&ItchioClient>,
+    game: &mut Game,
+    path: &Path,
+    connection: &mut SqliteConnection,
+) -> Result<()> {
+    // Media is opened with the default application of the system instead
+    if !game.classification.is_launchable() {
+        game.launch_target = None;
+        game.status = GameStatus::Installed;
+        game.update(connection)?;
+        return Ok(());
+    }
 
-    let payload = DownloadPayload {
-        game_id: game.id,
-        game_source: GameSource::Itchio,
-        game_title: game.title,
-        download_size: size,
-        downloaded: size,
+    let launch_targets = match client {
+        Some(client) => fetch_launch_targets(client, game, path).await,
+        None => Vec::new(),
     };
 
-    APP.get()
-        .unwrap()
-        .emit("download-finished", &payload)
-        .unwrap();
+    let launch_target = match launch_targets.first() {
+        Some(target) => Some(target.clone()),
+        // Fall back to looking for an executable if itch.io has no launch target for this platform
+        None => {
+            let target = match game.platform {
+                Some(GamePlatform::Windows) => util::fs::find_windows_launch_target(path).await?,
+                _ => util::fs::find_launch_target(path).await?,
+            };
 
-    post_download(game_id, path, file_name).await?;
+            let target = match target {
+                Some(target) => Some(target),
+                // Browser games are launched from their index.html
+                None => util::fs::find_file(path, "index.html").await?,
+            };
 
-    APP.get()
-        .unwrap()
-        .emit("download-installed", &payload)
-        .unwrap();
+            target.map(|target| {
+                target
+                    .strip_prefix(path)
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+        }
+    };
+
+    println!("Launch target: {:?}", launch_target);
+
+    #[cfg(unix)]
+    for target in launch_targets.iter().chain(launch_target.as_ref()) {
+        util::file::set_permissions(path.join(target), 0o755).await?;
+    }
+
+    LaunchTarget::replace(connection, &GameSource::Itchio, &game.id, &launch_targets)?;
+
+    game.launch_target = launch_target;
+    game.status = GameStatus::Installed;
+    game.update(connection)?;
 
     Ok(())
 }
 
+/// Opens the page of an externally hosted upload, so the user can start the download there.
+/// The download itself is then added to the download queue like any other.
 pub async fn handle_external_download(
     url: Url,
-    install_location: &Path,
+    download_options: DownloadOptions,
     game_id: &str,
     game_title: &str,
+    download_size: u64,
+    version: InstalledVersion,
 ) -> Result<()> {
-    if APP
-        .get()
-        .unwrap()
-        .get_webview_window("ichio-external")
-        .is_some()
-    {
-        return Err("External download window already open, wait for the download to finish before starting a new one".into());
+    let label = format!("itchio-external-{}", game_id);
+
+    if let Some(window) = APP.get().unwrap().get_webview_window(&label) {
+        window.set_focus()?;
+        return Ok(());
     }
 
-    let install_location = install_location.to_path_buf();
+    let download_started = Arc::new(AtomicBool::new(false));
+    let download_started_clone = download_started.clone();
     let game_id = game_id.to_string();
     let game_title = game_title.to_string();
-    WebviewWindow::builder(
-        APP.get().unwrap(),
-        "itchio-external",
-        WebviewUrl::External(url),
-    )
-    .on_download(move |webview, event| {
-        match event {
-            DownloadEvent::Requested { url, destination } => {
-                webview.window().hide().unwrap();
-                println!("downloading {}", url);
+    let game_title_clone = game_title.clone();
+    let game_id_clone = game_id.clone();
 
-                let mut connection = database::create_connection().unwrap();
-                let mut game =
-                    Game::select_one(&mut connection, &GameSource::Itchio, &game_id).unwrap();
-
-                game.status = GameStatus::Downloading;
-                game.update(&mut connection).unwrap();
-
-                *destination = install_location
-                    .clone()
-                    .join(destination.file_name().unwrap());
+    let window = WebviewWindow::builder(APP.get().unwrap(), label, WebviewUrl::External(url))
+        .on_download(move |webview, event| {
+            if let DownloadEvent::Requested { url, destination } = event {
+                println!("downloading {}", url);
 
-                APP.get()
-                    .unwrap()
-                    .emit(
-                        "download-external",
-                        DownloadPayload {
-                            game_id: game_id.clone(),
-                            game_source: GameSource::Itchio,
-                            game_title: game_title.clone(),
-                            download_size: 0,
-                            downloaded: 0,
-                        },
+                let file_name = match destination.file_name() {
+                    Some(file_name) => file_name.to_string_lossy().into_owned(),
+                    None => url
+                        .path_segments()
+                        .and_then(|mut segments| segments.next_back())
+                        .unwrap_or("download")
+                        .to_string(),
+                };
+
+                download_started_clone.store(true, Ordering::Relaxed);
+
+                let url = url.clone();
+                let game_id = game_id_clone.clone();
+                let game_title = game_title_clone.clone();
+                let download_options = download_options.clone();
+                let version = version.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = enqueue_external_download(
+                        url,
+                        file_name,
+                        download_options,
+                        &game_id,
+                        &game_title,
+                        download_size,
+                        version,
                     )
-                    .unwrap();
-            }
-            DownloadEvent::Finished { url, path, success } => {
-                println!("downloaded {} to {:?}, success: {}", url, path, success);
-
-                let path = path.unwrap();
-                let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
-                let directory = path.parent().unwrap().to_path_buf();
-
-                let game_id = game_id.clone();
-                tokio::spawn(async move {
-                    if let Err(e) =
-                        post_download_external(&game_id, directory, &file_name, success).await
+                    .await
                     {
-                        eprintln!("Failed to post download: {}", e);
+                        emit_external_download_failed(&game_id, &game_title, e.to_string());
                     }
                 });
-                webview.window().close().unwrap();
+
+                let _ = webview.window().close();
             }
-            _ => (),
-        }
-        // let the download start
-        true
-    })
-    .initialization_script(
-        r#"
+
+            // The download is handled by the download manager instead of the webview
+            false
+        })
+        .initialization_script(
+            r#"
             // Override window.open
             window.open = function (url, ...args) {
                 window.location.href = url;
@@ -407,8 +1270,255 @@ pub async fn handle_external_download(
                 }
             });
         "#,
-    )
-    .title("itch.io External Download")
-    .build()?;
+        )
+        .title(format!("{} - External Download", game_title))
+        .build()?;
+
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            if !download_started.load(Ordering::Relaxed) {
+                emit_external_download_failed(
+                    &game_id,
+                    &game_title,
+                    "The window was closed before the download started".to_string(),
+                );
+            }
+        }
+    });
+
     Ok(())
 }
+
+/// Adds an externally hosted file to the download queue. The game keeps its version until
+/// the file is installed.
+async fn enqueue_external_download(
+    url: Url,
+    file_name: String,
+    download_options: DownloadOptions,
+    game_id: &str,
+    game_title: &str,
+    download_size: u64,
+    version: InstalledVersion,
+) -> Result<()> {
+    let client = reqwest::Client::new();
+
+    // Prefer the size reported by the host, as the one from itch.io is often missing
+    let download_size = client
+        .head(url.clone())
+        .send()
+        .await
+        .ok()
+        .and_then(|response| response.content_length())
+        .unwrap_or(download_size);
+
+    let mut connection = database::create_connection()?;
+    let mut game = Game::select_one(&mut connection, &GameSource::Itchio, game_id)?;
+    let into_install = download_options.install_location.exists();
+    game.status = GameStatus::Downloading;
+    game.path = Some(
+        download_options
+            .install_location
+            .to_string_lossy()
+            .into_owned(),
+    );
+    game.update(&mut connection)?;
+
+    let download = Download {
+        files: vec![DownloadFile {
+            request: client.get(url),
+            file_name,
+            md5: None,
+        }],
+        download_options,
+        game_source: GameSource::Itchio,
+        game_id: game_id.to_string(),
+        game_title: game_title.to_string(),
+        download_size,
+        // The download can't be rebuilt, as the file is only found through the webview
+        version_id: None,
+    };
+
+    APP.get()
+        .unwrap()
+        .state::<DownloadManager>()
+        .enqueue_download(
+            download,
+            version,
+            into_install.then(|| game.installed_version()),
+        );
+
+    Ok(())
+}
+
+fn emit_external_download_failed(game_id: &str, game_title: &str, reason: String) {
+    println!("External download failed: {}", reason);
+
+    let payload = DownloadFailedPayload {
+        game_id: game_id.to_string(),
+        game_source: GameSource::Itchio,
+        game_title: game_title.to_string(),
+        reason,
+    };
+
+    if let Err(e) = APP.get().unwrap().emit("download-failed", payload) {
+        println!("Failed to emit download-failed: {}", e);
+    }
+}
+
+/// Receipt the itch app leaves in every install folder, describing what was installed.
+#[derive(Deserialize)]
+struct ItchAppReceipt {
+    game: Option<ItchAppReceiptGame>,
+    upload: Option<ItchAppReceiptUpload>,
+    build: Option<ItchAppReceiptBuild>,
+}
+
+#[derive(Deserialize)]
+struct ItchAppReceiptGame {
+    id: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItchAppReceiptUpload {
+    id: u32,
+    channel_name: Option<String>,
+    md5_hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ItchAppReceiptBuild {
+    version: u32,
+}
+
+#[derive(QueryableByName)]
+struct ItchAppInstallLocation {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    path: String,
+}
+
+/// Imports the games installed through the official itch app, so that they can be launched and
+/// updated from Fusion. Only games in the library that are not installed yet are imported.
+pub async fn import_itch_app_installs(client: &ItchioClient) -> Result<Vec<Game>> {
+    let mut connection = database::create_connection()?;
+    let mut imported_games = Vec::new();
+
+    for install_location in itch_app_install_locations()? {
+        let Ok(mut entries) = fs::read_dir(&install_location).await else {
+            continue;
+        };
+
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let install_folder = entry.path();
+            if !install_folder.is_dir() {
+                continue;
+            }
+
+            let Some(receipt) = read_itch_app_receipt(&install_folder).await else {
+                continue;
+            };
+
+            match import_itch_app_install(client, &mut connection, receipt, &install_folder).await {
+                Ok(Some(game)) => imported_games.push(game),
+                Ok(None) => (),
+                Err(e) => println!("Failed to import {:?}: {}", install_folder, e),
+            }
+        }
+    }
+
+    Ok(imported_games)
+}
+
+async fn import_itch_app_install(
+    client: &ItchioClient,
+    connection: &mut SqliteConnection,
+    receipt: ItchAppReceipt,
+    install_folder: &Path,
+) -> Result<Option<Game>> {
+    let (Some(receipt_game), Some(receipt_upload)) = (receipt.game, receipt.upload) else {
+        return Ok(None);
+    };
+
+    let Ok(mut game) = Game::select_one(
+        connection,
+        &GameSource::Itchio,
+        &receipt_game.id.to_string(),
+    ) else {
+        return Ok(None);
+    };
+
+    if game.status != GameStatus::NotInstalled {
+        return Ok(None);
+    }
+
+    let game_key: u32 = match &game.key {
+        Some(game_key) => game_key.parse()?,
+        None => return Ok(None),
+    };
+
+    let upload = client
+        .fetch_game_upload(receipt_upload.id, game_key)
+        .await
+        .ok();
+
+    // The installed build may be older than the current one, so it is taken from the receipt
+    game.version = receipt
+        .build
+        .map(|build| build.version.to_string())
+        .or(receipt_upload.md5_hash)
+        .or(upload.as_ref().map(upload_version));
+    game.version_id = Some(receipt_upload.id.to_string());
+    game.channel = receipt_upload.channel_name.or(upload
+        .as_ref()
+        .and_then(|upload| upload.channel_name.clone()));
+    game.platform = upload.as_ref().and_then(upload_platform);
+    game.path = Some(install_folder.to_string_lossy().into_owned());
+
+    if upload
+        .as_ref()
+        .is_some_and(|upload| upload.r#type == UploadType::Html)
+    {
+        save_game_embed(client, &game).await?;
+    }
+
+    install_launch_targets(Some(client), &mut game, install_folder, connection).await?;
+
+    Ok(Some(game))
+}
+
+/// Reads the install locations configured in the itch app, falling back to the default one.
+fn itch_app_install_locations() -> Result<Vec<PathBuf>> {
+    let itch_app_dir = APP.get().unwrap().path().config_dir()?.join("itch");
+    let database_path = itch_app_dir.join("db").join("butler.db");
+
+    let mut install_locations = vec![itch_app_dir.join("apps")];
+
+    if database_path.exists() {
+        let uri = format!("sqlite://{}?mode=ro", database_path.to_string_lossy());
+        let mut connection = SqliteConnection::establish(&uri)?;
+
+        let configured_locations: Vec<ItchAppInstallLocation> =
+            diesel::sql_query("SELECT path FROM install_locations").load(&mut connection)?;
+
+        for location in configured_locations {
+            let location = PathBuf::from(location.path);
+            if !install_locations.contains(&location) {
+                install_locations.push(location);
+            }
+        }
+    }
+
+    Ok(install_locations)
+}
+
+async fn read_itch_app_receipt(install_folder: &Path) -> Option<ItchAppReceipt> {
+    let receipt_path = install_folder.join(".itch").join("receipt.json.gz");
+    let compressed = fs::read(&receipt_path).await.ok()?;
+
+    let mut receipt = String::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut receipt)
+        .ok()?;
+
+    serde_json::from_str(&receipt).ok()
+}
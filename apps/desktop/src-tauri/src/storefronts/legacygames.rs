@@ -1,22 +1,30 @@
 use super::storefront::Storefront;
 use crate::{
     common::{database, result::Result},
-    managers::download::{Download, DownloadOptions},
+    managers::download::{Download, DownloadFile, DownloadOptions},
     models::{
         config::Config,
-        game::{Game, GameSource, GameStatus, GameVersion, GameVersionInfo},
+        game::{
+            Game, GameClassification, GamePlatform, GameSource, GameStatus, GameVersion,
+            GameVersionInfo, InstalledVersion,
+        },
+        install_size::InstallSize,
     },
     util, APP,
 };
 use async_trait::async_trait;
 use reqwest::header::ETAG;
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, RwLock},
 };
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tokio::{fs, task::JoinSet};
-use wrapper_legacygames::{api::models::Product, LegacyGamesClient};
+use wrapper_legacygames::{
+    api::models::{Installer, Product},
+    result::Error as LegacyGamesError,
+    LegacyGamesClient,
+};
 
 #[derive(Default)]
 pub struct LegacyGames {
@@ -55,55 +63,99 @@ impl Storefront for LegacyGames {
     }
 
     async fn fetch_games(&self) -> Result<Option<Vec<Game>>> {
-        if self.client.is_none() {
-            return Err("Legacy Games client is not initialized".into());
-        }
         let client = match &self.client {
             Some(c) => Arc::clone(c),
             None => return Ok(None),
         };
 
         let mut join_set = JoinSet::new();
+        let client_is_token_client = client.is_token_client();
 
-        if client.is_token_client() {
+        if client_is_token_client {
             let token_client = Arc::clone(&client);
 
-            join_set.spawn(async move {
-                token_client
-                    .fetch_wp_products()
-                    .await
-                    .map(|products| create_games(products, false))
-            });
+            join_set.spawn(async move { (false, token_client.fetch_wp_products().await) });
         }
 
-        join_set.spawn(async move {
-            client
-                .fetch_giveaway_products()
-                .await
-                .map(|products| create_games(products, true))
-        });
+        join_set.spawn(async move { (true, client.fetch_giveaway_products().await) });
 
-        let mut result = Vec::new();
+        let mut giveaway_products = Vec::new();
+        let mut wp_products = Vec::new();
+        let mut wp_products_fetched = !client_is_token_client;
 
         while let Some(res) = join_set.join_next().await {
             match res {
-                Ok(Ok(games)) => result.extend(games),
-                Ok(Err(e)) => return Err(e.into()),
+                Ok((true, Ok(products))) => giveaway_products = products,
+                Ok((true, Err(e))) => return Err(e.into()),
+                Ok((false, Ok(products))) => {
+                    wp_products = products;
+                    wp_products_fetched = true;
+                }
+                // The giveaway games are still returned when the purchased ones can't be fetched
+                Ok((false, Err(e))) => {
+                    if matches!(e, LegacyGamesError::Unauthorized(_)) {
+                        if let Err(e) = APP.get().unwrap().emit("legacy-games-login-required", ()) {
+                            println!("Error emitting legacy-games-login-required: {}", e);
+                        }
+                    }
+                    println!("Error fetching Legacy Games purchases: {}", e);
+                }
                 Err(e) => return Err(e.into()),
             }
         }
 
+        let mut connection = database::create_connection()?;
+
+        let mut estimates = install_size_estimates(&giveaway_products);
+        estimates.extend(install_size_estimates(&wp_products));
+        InstallSize::insert_estimates(&mut connection, &estimates)?;
+
+        let mut result = create_games(wp_products, false);
+
+        // A game can be both claimed in a giveaway and purchased. Only the purchase is kept,
+        // as it is not limited to the giveaway installer, and the giveaway entry is merged into it.
+        let mut duplicates = Vec::new();
+        for product in &mut giveaway_products {
+            product.games.retain(|game| {
+                // Without the purchases, the ones merged during a previous fetch are used
+                let purchased = result.iter().any(|purchase| purchase.id == game.game_id)
+                    || (!wp_products_fetched
+                        && Game::select_one(
+                            &mut connection,
+                            &GameSource::LegacyGames,
+                            &game.game_id,
+                        )
+                        .is_ok_and(|purchase| purchase.key.is_some()));
+                if purchased {
+                    duplicates.push((game.installer_uuid.clone(), game.game_id.clone()));
+                }
+                !purchased
+            });
+        }
+
+        for (installer_uuid, game_id) in duplicates {
+            if let Some(purchase) = result.iter().find(|purchase| purchase.id == game_id) {
+                if Game::merge_duplicate(&mut connection, &installer_uuid, purchase)? {
+                    if let Err(e) = move_prefix(&installer_uuid, &game_id).await {
+                        println!("Error moving the Wine prefix: {}", e);
+                    }
+                }
+            }
+        }
+
+        result.extend(create_games(giveaway_products, true));
+
         Ok(Some(result))
     }
 
     async fn fetch_game_versions(&self, game: Game) -> Result<Vec<GameVersion>> {
-        #[cfg(unix)]
-        return Ok(vec![]);
-
+        // The installers are Windows-only, they run through Wine on the other platforms
         Ok(vec![GameVersion {
             id: game.id.clone(),
             name: game.title,
             external: false,
+            requires_compatibility_layer: GamePlatform::native() != GamePlatform::Windows,
+            ..Default::default()
         }])
     }
 
@@ -118,18 +170,18 @@ impl Storefront for LegacyGames {
 
         let client = self.client.as_ref().unwrap();
 
-        let download_size = if let Some(ref key) = game.key {
-            client
-                .fetch_wp_installer_size(key.parse()?, &game.id)
-                .await?
-        } else {
-            client.fetch_giveaway_installer_size(&game.id).await?
-        } as u64;
+        let installer = self.fetch_installer(&game).await?;
+        let download_size = client.fetch_installer_size(&installer).await? as u64;
+
+        // The API doesn't give the installed size, the game_installed_size in its response is
+        // only an estimate. The actual size is known once the installer has been downloaded.
+        let mut connection = database::create_connection()?;
+        let install_size = InstallSize::select_one(&mut connection, &installer.installer_uuid)?
+            .map(|size| size.install_size as u64)
+            .unwrap_or_default();
 
-        // There is no way to fetch the installed size that I know.
-        // The game_installed_size in the API's resonse is actually the download size.
         Ok(GameVersionInfo {
-            install_size: 0,
+            install_size,
             download_size,
         })
     }
@@ -146,11 +198,8 @@ impl Storefront for LegacyGames {
 
         let client = self.client.as_ref().unwrap();
 
-        let installer_url = if let Some(ref key) = game.key {
-            client.fetch_wp_installer(key.parse()?, &game.id).await?
-        } else {
-            client.fetch_giveaway_installer(&game.id).await?
-        };
+        let installer = self.fetch_installer(game).await?;
+        let installer_url = installer.file.clone();
 
         let http = reqwest::Client::new();
 
@@ -161,25 +210,24 @@ impl Storefront for LegacyGames {
             .get(ETAG)
             .map(|header| header.to_str().unwrap().trim_matches('"').to_string());
 
-        let size = if let Some(ref key) = game.key {
-            client
-                .fetch_wp_installer_size(key.parse()?, &game.id)
-                .await?
-        } else {
-            client.fetch_giveaway_installer_size(&game.id).await?
-        };
+        let size = client.fetch_installer_size(&installer).await?;
 
         game.version = Some(game.id.clone());
+        game.version_id = Some(game.id.clone());
+        game.platform = Some(GamePlatform::Windows);
 
         Ok(Some(Download {
-            request: http.get(installer_url),
-            file_name: String::from("setup.exe"),
+            files: vec![DownloadFile {
+                request: http.get(installer_url),
+                file_name: String::from("setup.exe"),
+                md5,
+            }],
             download_options,
             game_source: GameSource::LegacyGames,
             game_id: game.id.clone(),
             game_title: game.title.clone(),
-            md5,
             download_size: size as u64,
+            version_id: game.version_id.clone(),
         }))
     }
 
@@ -189,7 +237,12 @@ impl Storefront for LegacyGames {
 
         let target_path = PathBuf::from(&game_path).join(&launch_target);
 
-        util::file::execute_file(&target_path)?;
+        if GamePlatform::native() == GamePlatform::Windows {
+            util::file::execute_file(&target_path)?;
+        } else {
+            let prefix = util::wine::prefix_path(&GameSource::LegacyGames, &game.id)?;
+            util::wine::execute_file(&target_path, Some(&prefix))?;
+        }
 
         Ok(())
     }
@@ -203,35 +256,123 @@ impl Storefront for LegacyGames {
             fs::remove_dir_all(&path).await?;
         }
 
+        let prefix = util::wine::prefix_path(&GameSource::LegacyGames, &game.id)?;
+        if prefix.exists() {
+            fs::remove_dir_all(&prefix).await?;
+        }
+
         Ok(())
     }
 
-    async fn post_download(&self, game_id: &str, path: PathBuf, file_name: &str) -> Result<()> {
-        let file_path = path.join(file_name);
+    async fn post_download(
+        &self,
+        game_id: &str,
+        version: InstalledVersion,
+        path: PathBuf,
+        file_names: Vec<String>,
+    ) -> Result<()> {
+        let file_path = path.join(&file_names[0]);
 
         let mut connection = database::create_connection()?;
         let mut game = Game::select_one(&mut connection, &GameSource::LegacyGames, game_id)?;
+        game.set_installed_version(version);
+
+        // The listing is only used to show the size to later installs, it is not required
+        if let Err(e) = self.save_install_size(&game, &file_path).await {
+            println!("Error computing the install size: {}", e);
+        }
 
         println!("Extracting game: {:?}", file_path);
         util::file::extract_file(&file_path, &path).await?;
 
-        let mut launch_target = util::fs::find_launch_target(&path).await?;
+        // The installer only contains Windows executables, which need no permissions on unix
+        // as they are run through Wine
+        let mut launch_target = util::fs::find_windows_launch_target(&path).await?;
 
         // Strip base path from launch target
         if let Some(target) = &launch_target {
-            #[cfg(unix)]
-            util::file::set_permissions(&target, 0o755).await?;
             launch_target = Some(target.strip_prefix(&path).unwrap().to_path_buf());
         }
 
         game.launch_target = launch_target.map(|target| target.to_string_lossy().into_owned());
+        game.platform = Some(GamePlatform::Windows);
         game.status = GameStatus::Installed;
-        game.update(&mut connection).unwrap();
+        game.update(&mut connection)?;
 
         Ok(())
     }
 }
 
+impl LegacyGames {
+    /// Fetches the installer of a game, using the purchase if there is one.
+    async fn fetch_installer(&self, game: &Game) -> Result<Installer> {
+        let client = match &self.client {
+            Some(c) => c,
+            None => return Err("Legacy Games client is not initialized".into()),
+        };
+
+        let installer = if let Some(ref key) = game.key {
+            client.fetch_wp_installer(key.parse()?, &game.id).await?
+        } else {
+            client.fetch_giveaway_installer(&game.id).await?
+        };
+
+        Ok(installer)
+    }
+
+    /// Computes the installed size from the downloaded installer and caches it.
+    async fn save_install_size(&self, game: &Game, file_path: &Path) -> Result<()> {
+        let installer = self.fetch_installer(game).await?;
+        let install_size = util::file::archive_size(file_path).await?;
+
+        let mut connection = database::create_connection()?;
+        InstallSize {
+            installer_uuid: installer.installer_uuid,
+            install_size: install_size as i64,
+            estimated: false,
+        }
+        .upsert(&mut connection)?;
+
+        Ok(())
+    }
+}
+
+/// Moves the Wine prefix of a game whose id changed, e.g. when it was merged into its purchase,
+/// so that its saves and settings are kept.
+async fn move_prefix(previous_id: &str, game_id: &str) -> Result<()> {
+    let previous_prefix = util::wine::prefix_path(&GameSource::LegacyGames, previous_id)?;
+    if !previous_prefix.exists() {
+        return Ok(());
+    }
+
+    // The game wasn't installed under its new id, so a prefix there is left from an old install
+    let prefix = util::wine::prefix_path(&GameSource::LegacyGames, game_id)?;
+    if prefix.exists() {
+        fs::remove_dir_all(&prefix).await?;
+    }
+
+    fs::rename(&previous_prefix, &prefix).await?;
+
+    Ok(())
+}
+
+/// Uses the sizes given by the API as estimates of the installed sizes.
+fn install_size_estimates(products: &[Product]) -> Vec<InstallSize> {
+    products
+        .iter()
+        .flat_map(|product| &product.games)
+        .filter_map(|game| {
+            let install_size = game.game_installed_size.trim().parse::<i64>().ok()?;
+
+            Some(InstallSize {
+                installer_uuid: game.installer_uuid.clone(),
+                install_size,
+                estimated: true,
+            })
+        })
+        .collect()
+}
+
 fn create_games(products: Vec<Product>, is_giveaway: bool) -> Vec<Game> {
     products
         .into_iter()
@@ -257,6 +398,12 @@ fn create_games(products: Vec<Product>, is_giveaway: bool) -> Vec<Game> {
                     hidden: false,
                     cover_url: Some(game.game_coverart),
                     sort_title: game.game_name.to_lowercase(),
+                    version_id: None,
+                    channel: None,
+                    owned: true,
+                    store_url: None,
+                    platform: None,
+                    classification: GameClassification::Game,
                 }
             })
         })
@@ -1,10 +1,15 @@
-use crate::models::game::GameSource;
+use crate::{
+    common::database,
+    models::game::{Game, GameSource, GameStatus},
+    APP,
+};
 use epicgames::EpicGames;
 use itchio::Itchio;
 use legacygames::LegacyGames;
 use std::sync::{Arc, OnceLock};
 use storefront::Storefront;
 use strum::IntoEnumIterator;
+use tauri::Emitter;
 use tokio::{sync::RwLock, task::JoinSet};
 
 pub mod epicgames;
@@ -53,3 +58,52 @@ pub async fn init_storefronts() -> Result<(), String> {
 
     Ok(())
 }
+
+/// Initializes a single storefront again, e.g. after the user logs in or out.
+pub async fn init_storefront(source: &GameSource) -> Result<(), String> {
+    get_storefront(source)
+        .write()
+        .await
+        .init()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Checks every installed game for updates, marking the outdated ones as such.
+pub async fn check_for_updates() -> Result<(), String> {
+    let mut connection = database::create_connection()?;
+
+    for mut game in Game::select_installed(&mut connection)? {
+        let update = get_storefront(&game.source)
+            .read()
+            .await
+            .fetch_game_update(&game)
+            .await;
+
+        match update {
+            Ok(Some(_)) if game.status != GameStatus::UpdateAvailable => {
+                game.status = GameStatus::UpdateAvailable;
+                game.update(&mut connection)?;
+
+                APP.get()
+                    .unwrap()
+                    .emit("game-update-available", &game)
+                    .map_err(|e| e.to_string())?;
+            }
+            // The update may have been installed outside of Fusion, or withdrawn
+            Ok(None) if game.status == GameStatus::UpdateAvailable => {
+                game.status = GameStatus::Installed;
+                game.update(&mut connection)?;
+
+                APP.get()
+                    .unwrap()
+                    .emit("game-updated", &game)
+                    .map_err(|e| e.to_string())?;
+            }
+            Ok(_) => (),
+            Err(e) => println!("Error checking for updates of {}: {}", game.title, e),
+        }
+    }
+
+    Ok(())
+}
@@ -5,13 +5,24 @@ use async_trait::async_trait;
 use crate::{
     common::result::Result,
     managers::download::{Download, DownloadOptions},
-    models::game::{Game, GameVersion, GameVersionInfo},
+    models::{
+        collection::FetchedCollection,
+        game::{Game, GameVerification, GameVersion, GameVersionInfo, InstalledVersion},
+    },
 };
 
 #[async_trait]
 pub trait Storefront {
     async fn init(&mut self) -> Result<()>;
     async fn fetch_games(&self) -> Result<Option<Vec<Game>>>;
+    /// Saves the state of the last fetch, once the fetched games are stored.
+    async fn finish_sync(&self) -> Result<()> {
+        Ok(())
+    }
+    /// Fetches the collections the user has created in the storefront, if it supports them.
+    async fn fetch_collections(&self) -> Result<Option<Vec<FetchedCollection>>> {
+        Ok(None)
+    }
     async fn fetch_game_versions(&self, game: Game) -> Result<Vec<GameVersion>>;
     async fn fetch_game_version_info(
         &self,
@@ -24,7 +35,35 @@ pub trait Storefront {
         version_id: String,
         download_options: DownloadOptions,
     ) -> Result<Option<Download>>;
-    async fn post_download(&self, game_id: &str, path: PathBuf, file_name: &str) -> Result<()>;
+    /// Installs the downloaded files. The version is only saved to the game once installed.
+    async fn post_download(
+        &self,
+        game_id: &str,
+        version: InstalledVersion,
+        path: PathBuf,
+        file_names: Vec<String>,
+    ) -> Result<()>;
+    /// Installs a game from a file that was downloaded outside of Fusion, e.g. through a browser.
+    async fn install_from_file(
+        &self,
+        _game: &mut Game,
+        _file_path: PathBuf,
+        _install_location: PathBuf,
+    ) -> Result<()> {
+        Err("Installing from a file is not supported for this storefront".into())
+    }
     async fn launch_game(&self, game: Game) -> Result<()>;
+    /// Returns the version an installed game should be updated to, if a newer one is available.
+    async fn fetch_game_update(&self, _game: &Game) -> Result<Option<String>> {
+        Ok(None)
+    }
+    /// Checks the files of an installed game against the ones published by the storefront.
+    async fn verify_game(&self, _game: &Game) -> Result<GameVerification> {
+        Err("Verifying games is not supported for this storefront".into())
+    }
+    /// Prepares the download that repairs the damaged files of an installed game.
+    async fn pre_repair(&self, _game: &mut Game) -> Result<Option<Download>> {
+        Err("Repairing games is not supported for this storefront".into())
+    }
     async fn uninstall_game(&self, game: &Game) -> Result<()>;
 }
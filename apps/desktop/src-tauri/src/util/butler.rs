@@ -0,0 +1,195 @@
+use crate::{common::result::Result, APP};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Output,
+};
+use tauri::Manager;
+use tokio::{fs, process::Command};
+
+#[cfg(windows)]
+const NO_WINDOW_FLAG: u32 = 0x08000000;
+
+#[cfg(windows)]
+const BUTLER_BINARY: &str = "butler.exe";
+#[cfg(not(windows))]
+const BUTLER_BINARY: &str = "butler";
+
+/// Error when butler is needed but can't be found.
+pub const NOT_FOUND: &str = "butler was not found. Install the itch app, or add butler to the PATH";
+
+/// Finds butler, used to handle wharf builds from itch.io. It is not bundled: the one
+/// installed by the itch app is used, or else one installed on its own and found on the PATH.
+pub fn find_butler() -> Option<PathBuf> {
+    itch_app_butler().or_else(|| {
+        env::var_os("PATH").and_then(|paths| {
+            env::split_paths(&paths)
+                .map(|dir| dir.join(BUTLER_BINARY))
+                .find(|butler| butler.is_file())
+        })
+    })
+}
+
+/// Finds the butler installed by the itch app, in `broth/butler/versions/<version>` under its
+/// data directory. The version it uses is preferred, then any other installed one.
+fn itch_app_butler() -> Option<PathBuf> {
+    let broth_dir = APP
+        .get()?
+        .path()
+        .config_dir()
+        .ok()?
+        .join("itch")
+        .join("broth")
+        .join("butler");
+    let versions_dir = broth_dir.join("versions");
+
+    if let Ok(version) = std::fs::read_to_string(broth_dir.join(".chosen-version")) {
+        let butler = versions_dir.join(version.trim()).join(BUTLER_BINARY);
+        if butler.is_file() {
+            return Some(butler);
+        }
+    }
+
+    std::fs::read_dir(versions_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(BUTLER_BINARY))
+        .filter(|butler| butler.is_file())
+        .max()
+}
+
+/// Whether wharf builds can be handled, i.e. patched and verified.
+pub fn is_available() -> bool {
+    find_butler().is_some()
+}
+
+fn butler_command() -> Result<Command> {
+    let butler = find_butler().ok_or(NOT_FOUND)?;
+
+    #[allow(unused_mut)]
+    let mut command = Command::new(butler);
+
+    #[cfg(windows)]
+    command.creation_flags(NO_WINDOW_FLAG);
+
+    Ok(command)
+}
+
+/// Applies a wharf patch (.pwr) in-place to the given directory.
+pub async fn apply_patch<P>(patch_path: &P, dir: &P, staging_dir: &P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let patch_path = patch_path.as_ref();
+    let dir = dir.as_ref();
+    let staging_dir = staging_dir.as_ref();
+
+    if !patch_path.exists() {
+        return Err(format!("Patch does not exist: {:?}", patch_path).into());
+    }
+
+    fs::create_dir_all(staging_dir).await?;
+
+    let result = butler_command()?
+        .arg("apply")
+        .arg(format!("--staging-dir={}", staging_dir.to_string_lossy()))
+        .arg(patch_path)
+        .arg(dir)
+        .output()
+        .await?;
+
+    println!(
+        "butler output: {:?}",
+        String::from_utf8_lossy(&result.stdout)
+    );
+
+    if !result.status.success() {
+        return Err(format!("Failed to apply patch: {:?}", patch_path).into());
+    }
+
+    Ok(())
+}
+
+/// Runs `butler verify` on a directory against a wharf signature (.pws), with extra arguments.
+async fn run_verify<P>(signature_path: &P, dir: &P, args: &[String]) -> Result<Output>
+where
+    P: AsRef<Path>,
+{
+    let signature_path = signature_path.as_ref();
+
+    if !signature_path.exists() {
+        return Err(format!("Signature does not exist: {:?}", signature_path).into());
+    }
+
+    let result = butler_command()?
+        .arg("verify")
+        .arg(signature_path)
+        .arg(dir.as_ref())
+        .args(args)
+        .output()
+        .await?;
+
+    Ok(result)
+}
+
+/// Verifies a directory against a wharf signature (.pws).
+pub async fn verify<P>(signature_path: &P, dir: &P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let result = run_verify(signature_path, dir, &[]).await?;
+
+    println!(
+        "butler output: {:?}",
+        String::from_utf8_lossy(&result.stdout)
+    );
+
+    if !result.status.success() {
+        return Err(format!("Failed to verify directory: {:?}", dir.as_ref()).into());
+    }
+
+    Ok(())
+}
+
+/// Checks a directory against a wharf signature (.pws), returning the problems butler found.
+/// Unlike `verify`, a damaged directory is not an error.
+pub async fn check<P>(signature_path: &P, dir: &P) -> Result<Vec<String>>
+where
+    P: AsRef<Path>,
+{
+    let result = run_verify(signature_path, dir, &[]).await?;
+
+    if result.status.success() {
+        return Ok(Vec::new());
+    }
+
+    let issues = String::from_utf8_lossy(&result.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&result.stderr).lines())
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    Ok(issues)
+}
+
+/// Repairs a directory against a wharf signature (.pws), taking the damaged or missing files
+/// from an archive of the same build.
+pub async fn heal<P>(signature_path: &P, dir: &P, archive_path: &P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let heal = format!("--heal=archive,{}", archive_path.as_ref().to_string_lossy());
+    let result = run_verify(signature_path, dir, &[heal]).await?;
+
+    println!(
+        "butler output: {:?}",
+        String::from_utf8_lossy(&result.stdout)
+    );
+
+    if !result.status.success() {
+        return Err(format!("Failed to heal directory: {:?}", dir.as_ref()).into());
+    }
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::{common::result::Result, APP};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 #[cfg(unix)]
 use std::{fs::Permissions, os::unix::fs::PermissionsExt};
 use tauri::{path::BaseDirectory, Manager};
@@ -38,20 +38,7 @@ where
         fs::create_dir_all(output_dir).await?;
     }
 
-    #[cfg(target_os = "windows")]
-    let os_specific_path = "thirdparty/7-Zip/windows/7z.exe";
-    #[cfg(target_os = "linux")]
-    let os_specific_path = "thirdparty/7-Zip/linux/7zzs";
-    #[cfg(target_os = "macos")]
-    let os_specific_path = "thirdparty/7-Zip/macos/7zz";
-
-    let seven_zip = APP
-        .get()
-        .unwrap()
-        .path()
-        .resolve(os_specific_path, BaseDirectory::Resource)?;
-
-    let mut command = tokio::process::Command::new(seven_zip);
+    let mut command = tokio::process::Command::new(seven_zip_path()?);
     command
         .arg("x")
         .arg(file_path)
@@ -75,6 +62,62 @@ where
     Ok(())
 }
 
+/// Computes the total size of the files in an archive, i.e. the space needed to extract it.
+/// The files skipped by `extract_file` are not counted.
+pub async fn archive_size<P>(file_path: &P) -> Result<u64>
+where
+    P: AsRef<Path>,
+{
+    let file_path = file_path.as_ref();
+
+    if !file_path.exists() {
+        return Err(format!("File does not exist: {:?}", file_path).into());
+    }
+
+    let mut command = tokio::process::Command::new(seven_zip_path()?);
+    command
+        .arg("l")
+        .arg("-slt")
+        .arg(file_path)
+        .arg("-x!$PLUGINSDIR/*");
+
+    #[cfg(windows)]
+    command.creation_flags(NO_WINDOW_FLAG);
+
+    let result = command.output().await?;
+
+    if !result.status.success() {
+        return Err(format!("Failed to list file: {:?}", file_path).into());
+    }
+
+    // The technical listing describes the archive itself first, then each entry after a separator
+    let size = String::from_utf8_lossy(&result.stdout)
+        .lines()
+        .skip_while(|line| !line.starts_with("----------"))
+        .filter_map(|line| line.strip_prefix("Size = "))
+        .filter_map(|size| size.trim().parse::<u64>().ok())
+        .sum();
+
+    Ok(size)
+}
+
+fn seven_zip_path() -> Result<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let os_specific_path = "thirdparty/7-Zip/windows/7z.exe";
+    #[cfg(target_os = "linux")]
+    let os_specific_path = "thirdparty/7-Zip/linux/7zzs";
+    #[cfg(target_os = "macos")]
+    let os_specific_path = "thirdparty/7-Zip/macos/7zz";
+
+    let seven_zip = APP
+        .get()
+        .unwrap()
+        .path()
+        .resolve(os_specific_path, BaseDirectory::Resource)?;
+
+    Ok(seven_zip)
+}
+
 pub fn execute_file<P>(file_path: &P) -> Result<()>
 where
     P: AsRef<Path>,
@@ -85,6 +128,16 @@ where
         return Err(format!("File does not exist: {:?}", file_path).into());
     }
 
+    // App bundles are directories, they are launched through the system
+    #[cfg(target_os = "macos")]
+    let mut command = if file_path.is_dir() {
+        let mut command = tokio::process::Command::new("open");
+        command.arg(file_path);
+        command
+    } else {
+        tokio::process::Command::new(file_path)
+    };
+    #[cfg(not(target_os = "macos"))]
     let mut command = tokio::process::Command::new(file_path);
 
     let parent_dir = file_path.parent().unwrap();
@@ -139,6 +192,31 @@ pub async fn is_executable(file_path: &Path) -> bool {
     false
 }
 
+/// Computes the MD5 hash of a file, as a lowercase hex string.
+pub async fn md5_file(file_path: &Path) -> Result<String> {
+    let mut file = File::open(file_path).await?;
+    let mut hasher = md5::Context::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.consume(&buffer[..read]);
+    }
+
+    Ok(format!("{:x}", hasher.compute()))
+}
+
+/// Checks whether the file is a Windows executable, regardless of the platform Fusion runs on.
+pub fn is_windows_executable(file_path: &Path) -> bool {
+    file_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("exe"))
+}
+
 #[cfg(unix)]
 pub async fn set_permissions<P: AsRef<Path>>(file_path: P, mode: u32) -> Result<()> {
     fs::set_permissions(file_path, Permissions::from_mode(mode)).await?;
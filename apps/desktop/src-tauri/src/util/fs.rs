@@ -22,12 +22,24 @@ pub async fn find_launch_target<P>(dir: P) -> Result<Option<PathBuf>>
 where
     P: AsRef<Path>,
 {
+    search_launch_target(dir.as_ref(), false).await
+}
+
+/// Like `find_launch_target`, but looks for Windows executables, e.g. to run them through Wine.
+pub async fn find_windows_launch_target<P>(dir: P) -> Result<Option<PathBuf>>
+where
+    P: AsRef<Path>,
+{
+    search_launch_target(dir.as_ref(), true).await
+}
+
+async fn search_launch_target(dir: &Path, windows: bool) -> Result<Option<PathBuf>> {
     let mut queue: VecDeque<PathBuf> = VecDeque::new();
-    queue.push_back(dir.as_ref().to_path_buf());
+    queue.push_back(dir.to_path_buf());
 
     while let Some(current_dir) = queue.pop_front() {
         #[cfg(target_os = "macos")]
-        if current_dir.to_string_lossy().ends_with(".app") {
+        if !windows && current_dir.to_string_lossy().ends_with(".app") {
             if let Ok(result) = find_launch_target_in_macos_app(&current_dir).await {
                 return Ok(Some(result));
             }
@@ -38,8 +50,14 @@ where
                 let path = entry.path();
                 if path.is_file() {
                     if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                        let executable = if windows {
+                            file::is_windows_executable(&path)
+                        } else {
+                            file::is_executable(&path).await
+                        };
+
                         if !BLACKLISTED_LAUNCH_TARGETS.contains(&name.to_lowercase().as_str())
-                            && file::is_executable(&path).await
+                            && executable
                         {
                             return Ok(Some(path));
                         }
@@ -55,6 +73,37 @@ where
     Ok(None)
 }
 
+/// Given a path, finds the closest file with the given name (e.g. the index.html of a browser game).
+pub async fn find_file<P>(dir: P, file_name: &str) -> Result<Option<PathBuf>>
+where
+    P: AsRef<Path>,
+{
+    let mut queue: VecDeque<PathBuf> = VecDeque::new();
+    queue.push_back(dir.as_ref().to_path_buf());
+
+    while let Some(current_dir) = queue.pop_front() {
+        if let Ok(mut entries) = fs::read_dir(&current_dir).await {
+            let mut subdirs = Vec::new();
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                if path.is_file() {
+                    if path
+                        .file_name()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(file_name))
+                    {
+                        return Ok(Some(path));
+                    }
+                } else if path.is_dir() {
+                    subdirs.push(path);
+                }
+            }
+            queue.extend(subdirs);
+        }
+    }
+
+    Ok(None)
+}
+
 #[cfg(target_os = "macos")]
 async fn find_launch_target_in_macos_app<P: AsRef<Path>>(app_dir: P) -> Result<PathBuf> {
     let app_dir = app_dir.as_ref();
@@ -0,0 +1,163 @@
+use crate::{
+    common::{database, result::Result},
+    models::game::{Game, GameEmbed, GameSource},
+    APP,
+};
+use std::path::{Component, Path, PathBuf};
+use tauri::{
+    http::{header::CONTENT_TYPE, Request, Response, StatusCode},
+    WebviewUrl, WebviewWindow,
+};
+use tokio::fs;
+
+/// URI scheme used to serve the files of installed browser games.
+pub const GAME_PROTOCOL: &str = "fusion-game";
+
+const DEFAULT_WIDTH: f64 = 1280.0;
+const DEFAULT_HEIGHT: f64 = 720.0;
+
+/// Opens a browser game in its own window, served from its install location.
+pub fn open_game_window(game: &Game, embed: Option<GameEmbed>) -> Result<()> {
+    let launch_target = game
+        .launch_target
+        .as_ref()
+        .ok_or("The game has no launch target")?;
+
+    let url = format!(
+        "{}/{}/{}/{}",
+        protocol_base_url(),
+        game.source,
+        game.id,
+        launch_target.replace('\\', "/")
+    );
+
+    let (width, height, fullscreen) = match embed {
+        Some(embed) => (embed.width as f64, embed.height as f64, embed.fullscreen),
+        None => (DEFAULT_WIDTH, DEFAULT_HEIGHT, false),
+    };
+
+    WebviewWindow::builder(
+        APP.get().unwrap(),
+        format!("game-{}-{}", game.source, game.id),
+        WebviewUrl::CustomProtocol(url.parse().map_err(|_| "Invalid game URL")?),
+    )
+    .title(&game.title)
+    .inner_size(width, height)
+    .fullscreen(fullscreen)
+    .build()?;
+
+    Ok(())
+}
+
+/// Serves a file of an installed browser game. The request path is
+/// `/<game source>/<game id>/<file path>`.
+pub async fn handle_protocol_request(request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    match read_game_file(request.uri().path()).await {
+        Ok((file_path, content)) => Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, mime_type(&file_path))
+            .body(content)
+            .unwrap(),
+        Err(e) => {
+            println!("Error serving game file: {}", e);
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Vec::new())
+                .unwrap()
+        }
+    }
+}
+
+async fn read_game_file(request_path: &str) -> Result<(PathBuf, Vec<u8>)> {
+    let request_path = percent_decode(request_path);
+    let mut segments = request_path.trim_start_matches('/').splitn(3, '/');
+
+    let (Some(game_source), Some(game_id), Some(file_path)) =
+        (segments.next(), segments.next(), segments.next())
+    else {
+        return Err("Invalid game file request".into());
+    };
+
+    let game_source: GameSource = game_source.parse().map_err(|_| "Invalid game source")?;
+
+    let mut connection = database::create_connection()?;
+    let game = Game::select_one(&mut connection, &game_source, game_id)?;
+    let game_path = game.path.ok_or("The game is not installed")?;
+
+    // Only serve files inside of the game directory
+    let file_path = Path::new(file_path);
+    if file_path
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err("Invalid game file path".into());
+    }
+
+    let file_path = PathBuf::from(game_path).join(file_path);
+    let content = fs::read(&file_path).await?;
+
+    Ok((file_path, content))
+}
+
+#[cfg(windows)]
+fn protocol_base_url() -> String {
+    format!("http://{}.localhost", GAME_PROTOCOL)
+}
+
+#[cfg(not(windows))]
+fn protocol_base_url() -> String {
+    format!("{}://localhost", GAME_PROTOCOL)
+}
+
+fn mime_type(file_path: &Path) -> &'static str {
+    let extension = file_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html",
+        "js" | "mjs" => "text/javascript",
+        "css" => "text/css",
+        "json" => "application/json",
+        "wasm" => "application/wasm",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "ttf" => "font/ttf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "txt" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
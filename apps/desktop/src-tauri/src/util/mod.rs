@@ -1,2 +1,5 @@
+pub mod butler;
 pub mod file;
 pub mod fs;
+pub mod html;
+pub mod wine;
@@ -0,0 +1,75 @@
+use crate::{
+    common::result::Result,
+    models::{config::Config, game::GameSource},
+    APP,
+};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+use tauri::Manager;
+use tokio::process::Command;
+
+const WINE_BINARY: &str = "wine";
+const PREFIXES_DIR: &str = "wine-prefixes";
+
+/// Finds the Wine runner to use, either the one set in the settings or the one on the PATH.
+pub fn find_runner() -> Option<PathBuf> {
+    let configured_runner = APP
+        .get()
+        .unwrap()
+        .state::<RwLock<Config>>()
+        .read()
+        .unwrap()
+        .wine_runner_path();
+
+    if let Some(runner) = configured_runner {
+        let runner = PathBuf::from(runner);
+        return runner.is_file().then_some(runner);
+    }
+
+    env::var_os("PATH").and_then(|paths| {
+        env::split_paths(&paths)
+            .map(|dir| dir.join(WINE_BINARY))
+            .find(|runner| runner.is_file())
+    })
+}
+
+/// Returns the Wine prefix dedicated to a game, so that games don't share their registry
+/// and their Windows files.
+pub fn prefix_path(source: &GameSource, game_id: &str) -> Result<PathBuf> {
+    let app_data_path = APP.get().unwrap().path().app_data_dir()?;
+    Ok(app_data_path
+        .join(PREFIXES_DIR)
+        .join(format!("{}-{}", source, game_id)))
+}
+
+/// Runs a Windows executable through Wine, in the given prefix or in the default one.
+pub fn execute_file<P>(file_path: &P, prefix: Option<&Path>) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let file_path = file_path.as_ref();
+
+    if !file_path.exists() {
+        return Err(format!("File does not exist: {:?}", file_path).into());
+    }
+
+    let runner = find_runner()
+        .ok_or("No Wine runner found, install Wine or set its path in the settings")?;
+
+    let mut command = Command::new(runner);
+    command.arg(file_path);
+    if let Some(prefix) = prefix {
+        std::fs::create_dir_all(prefix)?;
+        command.env("WINEPREFIX", prefix);
+    }
+    command.current_dir(file_path.parent().unwrap());
+
+    let result = command.spawn().map_err(|e| e.to_string())?;
+
+    println!("{:?}", result);
+
+    Ok(())
+}
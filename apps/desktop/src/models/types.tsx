@@ -46,3 +46,9 @@ export interface DownloadItem {
   downloadSize: number;
   downloaded: number;
 }
+
+export interface Downloads {
+  active: DownloadItem[];
+  queued: DownloadItem[];
+  paused: DownloadItem[];
+}
@@ -0,0 +1,6 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { Downloads } from "../models/types";
+
+export async function getDownloads(): Promise<Downloads> {
+  return invoke<Downloads>("get_downloads");
+}
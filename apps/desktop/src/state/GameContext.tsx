@@ -5,6 +5,7 @@ import type { SetStoreFunction } from "solid-js/store";
 import { createStore, produce } from "solid-js/store";
 import { listen } from "@tauri-apps/api/event";
 import { getGames as getGamesFromBackend } from "../services/game";
+import { getDownloads } from "../services/download";
 
 export const GameContext = createContext<{
   state: {
@@ -203,6 +204,17 @@ const ContextProvider = (props: StateProps) => {
     },
   );
 
+  // Downloads restored at startup may have been announced before the listeners were set up
+  downloadQueuedUnlisten
+    .then(() => getDownloads())
+    .then((downloads) => {
+      setState("downloadQueue", [
+        ...downloads.active,
+        ...downloads.queued,
+        ...downloads.paused,
+      ]);
+    });
+
   onCleanup(() => {
     gameHiddenUnlisten.then((u) => u());
     gameUninstallingUnlisten.then((u) => u());
@@ -5,6 +5,11 @@ pub fn owned_keys(page: u32) -> String {
     format!("{}/profile/owned-keys?page={}", BASE_URL, page)
 }
 
+/// A specific game.
+pub fn game(game_id: u32) -> String {
+    format!("{}/games/{}", BASE_URL, game_id)
+}
+
 /// The list of uploads for a game.
 pub fn uploads(game_id: u32, download_key_id: u32) -> String {
     format!(
@@ -61,6 +66,19 @@ pub fn build_scanned_archive(build_id: u32, download_key_id: u32) -> String {
     )
 }
 
+/// The download URL for a file of a build (archive, patch, signature...).
+pub fn build_download(
+    build_id: u32,
+    file_type: &str,
+    file_sub_type: &str,
+    download_key_id: u32,
+) -> String {
+    format!(
+        "{}/builds/{}/download/{}/{}?download_key_id={}",
+        BASE_URL, build_id, file_type, file_sub_type, download_key_id
+    )
+}
+
 /// The list of collections that the user has created.
 pub fn collections() -> String {
     format!("{}/profile/collections", BASE_URL)
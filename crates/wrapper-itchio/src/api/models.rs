@@ -59,7 +59,7 @@ pub struct TOTPLoginParams {
 
 #[derive(Debug, Deserialize)]
 pub struct OwnedKeys {
-    pub per_page: u8,
+    pub per_page: u32,
     pub page: u32,
     #[serde(deserialize_with = "deserialize_empty_object")]
     pub owned_keys: Vec<OwnedKey>,
@@ -78,6 +78,11 @@ pub struct OwnedKey {
     pub game: Game,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct GameResponse {
+    pub game: Game,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Game {
     pub id: u32,
@@ -195,6 +200,23 @@ pub struct Build {
     pub updated_at: NaiveDateTime,
 }
 
+impl Build {
+    /// Finds an uploaded file of the given type, preferring the optimized variant if there is one.
+    pub fn file(&self, file_type: BuildFileType) -> Option<&BuildFile> {
+        let files: Vec<&BuildFile> = self
+            .files
+            .iter()
+            .filter(|file| file.r#type == file_type && file.state == BuildFileState::Uploaded)
+            .collect();
+
+        files
+            .iter()
+            .find(|file| file.sub_type == BuildFileSubType::Optimized)
+            .or(files.first())
+            .copied()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BuildFile {
     pub size: u32,
@@ -256,12 +278,13 @@ pub struct ScannedArchive {
 
 #[derive(Deserialize, Debug)]
 pub struct LaunchTarget {
+    #[serde(default)]
     pub arch: String,
     pub flavor: String,
     pub path: String,
     pub sha256: String,
     pub size: u32,
-    pub pe_info: PeInfo,
+    pub pe_info: Option<PeInfo>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -342,7 +365,7 @@ pub enum UploadTraits {
     Demo,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum UploadType {
     Default,
@@ -361,6 +384,27 @@ pub enum UploadType {
     Other,
 }
 
+impl UploadType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Flash => "flash",
+            Self::Unity => "unity",
+            Self::Java => "java",
+            Self::Html => "html",
+            Self::Soundtrack => "soundtrack",
+            Self::Book => "book",
+            Self::Video => "video",
+            Self::Documentation => "documentation",
+            Self::Mod => "mod",
+            Self::AudioAssets => "audio_assets",
+            Self::GraphicalAssets => "graphical_assets",
+            Self::Sourcecode => "sourcecode",
+            Self::Other => "other",
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum BuildState {
@@ -370,7 +414,7 @@ pub enum BuildState {
     Failed,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum BuildFileState {
     Created,
@@ -379,7 +423,7 @@ pub enum BuildFileState {
     Failed,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum BuildFileType {
     Patch,
@@ -389,7 +433,19 @@ pub enum BuildFileType {
     Unpacked,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+impl BuildFileType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Patch => "patch",
+            Self::Archive => "archive",
+            Self::Signature => "signature",
+            Self::Manifest => "manifest",
+            Self::Unpacked => "unpacked",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum BuildFileSubType {
     Default,
@@ -397,6 +453,16 @@ pub enum BuildFileSubType {
     Optimized,
 }
 
+impl BuildFileSubType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Gzip => "gzip",
+            Self::Optimized => "optimized",
+        }
+    }
+}
+
 // Deserialize date strings into actual dates
 fn deserialize_date<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
 where
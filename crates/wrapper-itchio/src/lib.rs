@@ -1,13 +1,15 @@
 pub mod api;
+pub mod result;
 mod tests;
 
 use api::models::{
-    Build, BuildResponse, Builds, Collection, CollectionGames, CollectionsResponse, Login,
-    LoginParams, OwnedKeys, ScannedArchive, ScannedArchiveResponse, TOTPLoginParams, Upload,
-    UploadResponse, Uploads,
+    Build, BuildFileSubType, BuildFileType, BuildResponse, Builds, Collection, CollectionGames,
+    CollectionsResponse, Game, GameResponse, Login, LoginParams, OwnedKeys, ScannedArchive,
+    ScannedArchiveResponse, TOTPLoginParams, Upload, UploadResponse, Uploads,
 };
 use reqwest::header::CONTENT_TYPE;
-use reqwest::RequestBuilder;
+use reqwest::{RequestBuilder, Response};
+use result::{ApiErrors, Error, Result};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
@@ -26,17 +28,25 @@ impl ItchioClient {
     }
 
     /// Fetches the list of keys (games) that the user owns.
-    pub async fn fetch_owned_keys(&self, page: u32) -> Result<OwnedKeys, reqwest::Error> {
+    pub async fn fetch_owned_keys(&self, page: u32) -> Result<OwnedKeys> {
         self.make_get_request(&api::endpoints::owned_keys(page))
             .await
     }
 
+    /// Fetches a specific game.
+    pub async fn fetch_game(&self, game_id: u32) -> Result<Game> {
+        let response: GameResponse = self
+            .make_get_request(&api::endpoints::game(game_id))
+            .await?;
+        Ok(response.game)
+    }
+
     /// Fetches the list of uploads for a game.
     pub async fn fetch_game_uploads(
         &self,
         game_id: u32,
         download_key_id: u32,
-    ) -> Result<Vec<Upload>, reqwest::Error> {
+    ) -> Result<Vec<Upload>> {
         let response: Uploads = self
             .make_get_request(&api::endpoints::uploads(game_id, download_key_id))
             .await?;
@@ -44,11 +54,7 @@ impl ItchioClient {
     }
 
     /// Fetches a specific upload.
-    pub async fn fetch_game_upload(
-        &self,
-        upload_id: u32,
-        download_key_id: u32,
-    ) -> Result<Upload, reqwest::Error> {
+    pub async fn fetch_game_upload(&self, upload_id: u32, download_key_id: u32) -> Result<Upload> {
         let response: UploadResponse = self
             .make_get_request(&api::endpoints::upload(upload_id, download_key_id))
             .await?;
@@ -60,7 +66,7 @@ impl ItchioClient {
         &self,
         upload_id: u32,
         download_key_id: u32,
-    ) -> Result<ScannedArchive, reqwest::Error> {
+    ) -> Result<ScannedArchive> {
         let response: ScannedArchiveResponse = self
             .make_get_request(&api::endpoints::upload_scanned_archive(
                 upload_id,
@@ -86,29 +92,43 @@ impl ItchioClient {
         &self,
         upload_id: u32,
         download_key_id: u32,
-    ) -> Result<Builds, reqwest::Error> {
+    ) -> Result<Builds> {
         self.make_get_request(&api::endpoints::builds(upload_id, download_key_id))
             .await
     }
 
     /// Fetches a specific build.
-    pub async fn fetch_upload_build(
-        &self,
-        build_id: u32,
-        download_key_id: u32,
-    ) -> Result<Build, reqwest::Error> {
+    pub async fn fetch_upload_build(&self, build_id: u32, download_key_id: u32) -> Result<Build> {
         let response: BuildResponse = self
             .make_get_request(&api::endpoints::build(build_id, download_key_id))
             .await?;
         Ok(response.build)
     }
 
+    /// Fetches the download URL for a file of a build, such as a wharf patch or signature.
+    pub fn fetch_build_download_url(
+        &self,
+        build_id: u32,
+        file_type: &BuildFileType,
+        file_sub_type: &BuildFileSubType,
+        download_key_id: u32,
+    ) -> RequestBuilder {
+        self.http
+            .get(api::endpoints::build_download(
+                build_id,
+                file_type.as_str(),
+                file_sub_type.as_str(),
+                download_key_id,
+            ))
+            .header("Authorization", &self.api_key)
+    }
+
     /// Fetches information about the scanned build.
     pub async fn fetch_build_scanned_archive(
         &self,
         build_id: u32,
         download_key_id: u32,
-    ) -> Result<ScannedArchive, reqwest::Error> {
+    ) -> Result<ScannedArchive> {
         let response: ScannedArchiveResponse = self
             .make_get_request(&api::endpoints::build_scanned_archive(
                 build_id,
@@ -119,7 +139,7 @@ impl ItchioClient {
     }
 
     /// Fetches the list of collections that the user has created.
-    pub async fn fetch_collections(&self) -> Result<Vec<Collection>, reqwest::Error> {
+    pub async fn fetch_collections(&self) -> Result<Vec<Collection>> {
         let response: CollectionsResponse = self
             .make_get_request(&api::endpoints::collections())
             .await?;
@@ -131,13 +151,13 @@ impl ItchioClient {
         &self,
         collection_id: u32,
         page: u32,
-    ) -> Result<CollectionGames, reqwest::Error> {
+    ) -> Result<CollectionGames> {
         self.make_get_request(&api::endpoints::collection_games(collection_id, page))
             .await
     }
 
     /// Static function to login to the itch.io API.
-    pub async fn login(username: String, password: String) -> Result<Login, reqwest::Error> {
+    pub async fn login(username: String, password: String) -> Result<Login> {
         Self::login_with_recaptcha(username, password, String::from("")).await
     }
 
@@ -148,7 +168,7 @@ impl ItchioClient {
         username: String,
         password: String,
         recaptcha: String,
-    ) -> Result<Login, reqwest::Error> {
+    ) -> Result<Login> {
         let params = LoginParams {
             source: "desktop",
             username,
@@ -166,7 +186,7 @@ impl ItchioClient {
     }
 
     /// Static function to login to the itch.io API using TOTP.
-    pub async fn totp_verify(code: String, token: String) -> Result<Login, reqwest::Error> {
+    pub async fn totp_verify(code: String, token: String) -> Result<Login> {
         let params = TOTPLoginParams { token, code };
 
         let response: Login =
@@ -176,34 +196,54 @@ impl ItchioClient {
     }
 
     /// Makes a GET request to the itch.io API.
-    async fn make_get_request<D>(&self, url: &str) -> Result<D, reqwest::Error>
+    async fn make_get_request<D>(&self, url: &str) -> Result<D>
     where
         D: DeserializeOwned,
     {
-        self.http
+        let response = self
+            .http
             .get(url)
             .header("Authorization", &self.api_key)
             .send()
-            .await?
-            .json()
-            .await
+            .await?;
+
+        Self::parse_response(response).await
     }
 
     /// Makes a POST request to the itch.io API.
     /// This function does not depend on self, so it can be called statically
     /// even without an API key.
-    async fn make_post_request<D, S>(url: &str, params: S) -> Result<D, reqwest::Error>
+    async fn make_post_request<D, S>(url: &str, params: S) -> Result<D>
     where
         D: DeserializeOwned,
         S: Serialize,
     {
-        reqwest::Client::new()
+        let response = reqwest::Client::new()
             .post(url)
             .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
             .body(serde_urlencoded::to_string(&params).unwrap())
             .send()
-            .await?
-            .json()
-            .await
+            .await?;
+
+        Self::parse_response(response).await
+    }
+
+    /// Parses the body of an API response, turning the errors returned by itch.io into typed ones.
+    async fn parse_response<D>(response: Response) -> Result<D>
+    where
+        D: DeserializeOwned,
+    {
+        let status = response.status();
+        let body = response.text().await?;
+
+        if let Ok(api_errors) = serde_json::from_str::<ApiErrors>(&body) {
+            return Err(Error::from_api_errors(status, api_errors.errors));
+        }
+
+        if !status.is_success() {
+            return Err(Error::from_api_errors(status, Vec::new()));
+        }
+
+        Ok(serde_json::from_str(&body)?)
     }
 }
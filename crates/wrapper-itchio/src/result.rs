@@ -0,0 +1,96 @@
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The API key or the login credentials were rejected.
+    Unauthorized(String),
+    /// The requested resource does not exist, or the download key does not give access to it.
+    NotFound(String),
+    /// Too many requests were made in a short time.
+    RateLimited(String),
+    /// Any other error returned by the itch.io API.
+    Api(String),
+    Json(serde_json::Error),
+    Reqwest(reqwest::Error),
+}
+
+/// Body returned by the itch.io API when a request fails.
+#[derive(Deserialize, Debug)]
+pub(crate) struct ApiErrors {
+    pub errors: Vec<String>,
+}
+
+impl Error {
+    /// Classifies the errors returned by the itch.io API, using both the status code and
+    /// the messages, as some errors are returned with a successful status.
+    pub(crate) fn from_api_errors(status: StatusCode, errors: Vec<String>) -> Self {
+        let message = if errors.is_empty() {
+            status.to_string()
+        } else {
+            errors.join(", ")
+        };
+        let lowercase_message = message.to_lowercase();
+        let message_contains = |patterns: &[&str]| {
+            patterns
+                .iter()
+                .any(|pattern| lowercase_message.contains(pattern))
+        };
+
+        if status == StatusCode::TOO_MANY_REQUESTS || message_contains(&["rate limit", "too many"])
+        {
+            Self::RateLimited(message)
+        } else if status == StatusCode::UNAUTHORIZED
+            || status == StatusCode::FORBIDDEN
+            || message_contains(&["invalid key", "api key", "logged in", "password"])
+        {
+            Self::Unauthorized(message)
+        } else if status == StatusCode::NOT_FOUND
+            || message_contains(&[
+                "not found",
+                "download key",
+                "invalid game",
+                "invalid upload",
+                "invalid build",
+                "invalid collection",
+            ])
+        {
+            Self::NotFound(message)
+        } else {
+            Self::Api(message)
+        }
+    }
+}
+
+impl StdError for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Reqwest(e)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Unauthorized(e) => write!(f, "itch.io rejected the credentials: {}", e),
+            Self::NotFound(e) => write!(f, "itch.io resource not found: {}", e),
+            Self::RateLimited(e) => write!(f, "itch.io rate limit reached, try again later: {}", e),
+            Self::Api(e) => write!(f, "itch.io API error: {}", e),
+            Self::Json(e) => write!(f, "JSON error: {}", e),
+            Self::Reqwest(e) => write!(f, "Reqwest error: {}", e),
+        }
+    }
+}
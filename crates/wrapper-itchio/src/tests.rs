@@ -1,7 +1,8 @@
 #[cfg(test)]
 #[allow(clippy::module_inception)]
 mod tests {
-    use crate::*;
+    use crate::{result::Error, *};
+    use reqwest::StatusCode;
 
     #[tokio::test]
     async fn owned_keys_test() {
@@ -11,6 +12,14 @@ mod tests {
         assert!(owned_keys.is_ok());
     }
 
+    #[tokio::test]
+    async fn game_test() {
+        let client = ItchioClient::new("abcd");
+        let game = client.fetch_game(204750).await;
+        println!("{:?}", game);
+        assert!(game.is_ok());
+    }
+
     #[tokio::test]
     async fn uploads_test() {
         let client = ItchioClient::new("abcd");
@@ -59,17 +68,34 @@ mod tests {
         assert!(collection_games.is_ok());
     }
 
+    // Invalid credentials are rejected by itch.io, but the response must still be understood
     #[tokio::test]
     async fn test_login() {
         let login = ItchioClient::login(String::from("ABCD"), String::from("abcd")).await;
         println!("{:?}", login);
-        assert!(login.is_ok());
+        assert!(!matches!(login, Err(Error::Json(_) | Error::Reqwest(_))));
     }
 
     #[tokio::test]
     async fn test_totp_verify() {
         let login = ItchioClient::totp_verify(String::from("ABCD"), String::from("abcd")).await;
         println!("{:?}", login);
-        assert!(login.is_ok());
+        assert!(!matches!(login, Err(Error::Json(_) | Error::Reqwest(_))));
+    }
+
+    #[test]
+    fn api_errors_test() {
+        let error = Error::from_api_errors(StatusCode::BAD_REQUEST, vec!["invalid key".into()]);
+        assert!(matches!(error, Error::Unauthorized(_)));
+
+        let error =
+            Error::from_api_errors(StatusCode::BAD_REQUEST, vec!["invalid download key".into()]);
+        assert!(matches!(error, Error::NotFound(_)));
+
+        let error = Error::from_api_errors(StatusCode::TOO_MANY_REQUESTS, Vec::new());
+        assert!(matches!(error, Error::RateLimited(_)));
+
+        let error = Error::from_api_errors(StatusCode::OK, vec!["something went wrong".into()]);
+        assert!(matches!(error, Error::Api(_)));
     }
 }
@@ -1,11 +1,12 @@
 use api::models::{
-    InstallerResponse, InstallerResponseData, IsExistsByEmail, Product, Products, ProductsData,
-    TestLogin,
+    Installer, InstallerResponse, InstallerResponseData, IsExistsByEmail, Product, Products,
+    ProductsData, Status, TestLogin,
 };
 use base64::{prelude::BASE64_STANDARD, Engine};
 use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE};
-use result::Result;
+use result::{Error, Result};
 use serde::de::DeserializeOwned;
+use std::sync::OnceLock;
 use tokio::try_join;
 
 pub mod api;
@@ -15,6 +16,8 @@ mod tests;
 pub struct LegacyGamesClient {
     email: String,
     token: Option<String>,
+    /// The id of the WordPress user, known after the token has been checked once.
+    user_id: OnceLock<u32>,
     http: reqwest::Client,
 }
 
@@ -25,6 +28,7 @@ impl LegacyGamesClient {
         Self {
             email,
             token: None,
+            user_id: OnceLock::new(),
             http: reqwest::Client::new(),
         }
     }
@@ -35,6 +39,7 @@ impl LegacyGamesClient {
         Self {
             email,
             token: Some(token),
+            user_id: OnceLock::new(),
             http: reqwest::Client::new(),
         }
     }
@@ -62,34 +67,21 @@ impl LegacyGamesClient {
     }
 
     /// Fetches the installer for a giveaway game.
-    pub async fn fetch_giveaway_installer(&self, installer_uuid: &str) -> Result<String> {
+    pub async fn fetch_giveaway_installer(&self, installer_uuid: &str) -> Result<Installer> {
         let response: InstallerResponse = self
             .make_get_request(&api::endpoints::giveaway_installer(installer_uuid))
             .await?;
 
         match response.data {
-            InstallerResponseData::Installer(installer) => Ok(installer.file),
+            InstallerResponseData::Installer(installer) => Ok(installer),
             InstallerResponseData::Error(e) => Err(e.into()),
         }
     }
 
     /// Fetches the size of the installer for a giveaway game.
     pub async fn fetch_giveaway_installer_size(&self, installer_uuid: &str) -> Result<u32> {
-        let response: InstallerResponse = self
-            .make_get_request(&api::endpoints::giveaway_installer(installer_uuid))
-            .await?;
-
-        if let InstallerResponseData::Installer(installer) = response.data {
-            let response = self.make_head_request(&installer.file).await?;
-
-            if let Some(content_length) = response.headers().get(CONTENT_LENGTH) {
-                Ok(content_length.to_str().unwrap().parse::<u32>()?)
-            } else {
-                Ok(0)
-            }
-        } else {
-            Err("Installer not found".into())
-        }
+        let installer = self.fetch_giveaway_installer(installer_uuid).await?;
+        self.fetch_installer_size(&installer).await
     }
 
     /// Fetches the purchased games. Note that a bearer token is required.
@@ -98,20 +90,52 @@ impl LegacyGamesClient {
             return Err("Token required".into());
         }
 
-        let login = Self::test_login(self.token.clone().unwrap()).await?;
+        let response: Result<Products> = self
+            .make_get_request(&api::endpoints::user_downloads(self.fetch_user_id().await?))
+            .await;
 
-        let response: Products = self
-            .make_get_request(&api::endpoints::user_downloads(login.data.user_id.unwrap()))
-            .await?;
+        if let Ok(Products {
+            data: ProductsData::Products(products),
+            ..
+        }) = response
+        {
+            return Ok(products);
+        }
 
-        match response.data {
-            ProductsData::Products(products) => Ok(products),
-            ProductsData::Error(_) => Ok(Vec::new()),
+        // The user id is only checked once, so a token that expired since then is noticed here
+        self.check_token().await?;
+
+        match response {
+            Ok(_) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetches the id of the WordPress user, which is only checked once per client.
+    pub async fn fetch_user_id(&self) -> Result<u32> {
+        if let Some(user_id) = self.user_id.get() {
+            return Ok(*user_id);
+        }
+
+        let user_id = self.check_token().await?;
+
+        Ok(*self.user_id.get_or_init(|| user_id))
+    }
+
+    /// Checks that the token is still accepted, returning the id of its user.
+    async fn check_token(&self) -> Result<u32> {
+        let login = Self::test_login(self.token.clone().ok_or("Token required")?).await?;
+
+        match (login.status, login.data.user_id) {
+            (Status::Ok, Some(user_id)) => Ok(user_id),
+            _ => Err(Error::Unauthorized(
+                login.data.message.unwrap_or_else(|| "Invalid token".into()),
+            )),
         }
     }
 
     /// Fetches the installer for a purchased game.
-    pub async fn fetch_wp_installer(&self, product_id: u32, game_id: &str) -> Result<String> {
+    pub async fn fetch_wp_installer(&self, product_id: u32, game_id: &str) -> Result<Installer> {
         if self.is_email_client() {
             return Err("Token required".into());
         }
@@ -121,31 +145,25 @@ impl LegacyGamesClient {
             .await?;
 
         match response.data {
-            InstallerResponseData::Installer(installer) => Ok(installer.file),
+            InstallerResponseData::Installer(installer) => Ok(installer),
             InstallerResponseData::Error(e) => Err(e.into()),
         }
     }
 
     /// Fetches the size of the installer for a purchased game.
     pub async fn fetch_wp_installer_size(&self, product_id: u32, game_id: &str) -> Result<u32> {
-        if self.is_email_client() {
-            return Err("Token required".into());
-        }
-
-        let response: InstallerResponse = self
-            .make_get_request(&api::endpoints::wp_installer(product_id, game_id))
-            .await?;
+        let installer = self.fetch_wp_installer(product_id, game_id).await?;
+        self.fetch_installer_size(&installer).await
+    }
 
-        if let InstallerResponseData::Installer(installer) = response.data {
-            let response = self.make_head_request(&installer.file).await?;
+    /// Fetches the size of an installer, without downloading it.
+    pub async fn fetch_installer_size(&self, installer: &Installer) -> Result<u32> {
+        let response = self.make_head_request(&installer.file).await?;
 
-            if let Some(content_length) = response.headers().get(CONTENT_LENGTH) {
-                Ok(content_length.to_str().unwrap().parse::<u32>()?)
-            } else {
-                Ok(0)
-            }
+        if let Some(content_length) = response.headers().get(CONTENT_LENGTH) {
+            Ok(content_length.to_str().unwrap().parse::<u32>()?)
         } else {
-            Err("Installer not found".into())
+            Ok(0)
         }
     }
 
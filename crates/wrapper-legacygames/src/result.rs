@@ -7,6 +7,8 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
+    /// The token was rejected, e.g. because the password was changed.
+    Unauthorized(String),
     ParseInt(std::num::ParseIntError),
     Reqwest(reqwest::Error),
     Other(String),
@@ -41,6 +43,7 @@ impl From<&str> for Error {
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
+            Self::Unauthorized(e) => write!(f, "Legacy Games rejected the credentials: {}", e),
             Self::ParseInt(e) => write!(f, "Parse int error: {}", e),
             Self::Reqwest(e) => write!(f, "Reqwest error: {}", e),
             Self::Other(e) => write!(f, "Other error: {}", e),